@@ -0,0 +1,132 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use wurm::Ignore;
+
+use owlengine::uci::{
+    msg::{Command, Message},
+    parse::{self, Parse},
+};
+
+mod common;
+
+const HANDSHAKE: &str = "\
+> uci
+< id name Stockfish 16
+< id author the Stockfish developers (see AUTHORS file)
+< option name Debug Log File type string default <empty>
+< option name Threads type spin default 1 min 1 max 1024
+< option name Hash type spin default 16 min 1 max 33554432
+< option name Clear Hash type button
+< option name Ponder type check default false
+< option name MultiPV type spin default 1 min 1 max 500
+< option name Skill Level type spin default 20 min 0 max 20
+< option name Move Overhead type spin default 10 min 0 max 5000
+< option name UCI_Chess960 type check default false
+< option name UCI_LimitStrength type check default false
+< option name UCI_Elo type spin default 1320 min 1320 max 3190
+< option name SyzygyPath type string default <empty>
+< option name Use NNUE type check default true
+< option name EvalFile type string default nn-5af11540bbfe.nnue
+< uciok
+> setoption name Threads value 8
+> setoption name Hash value 1024
+> isready
+< readyok
+> ucinewgame
+> isready
+< readyok
+";
+
+const MAX_DEPTH: usize = 24;
+
+/// Builds a synthetic session in Stockfish output format: handshake, then a game where each move
+/// is searched with `position` and `go`, producing a few thousand `info` lines
+///
+/// The PVs are taken from the game continuation, so all the moves in the session are legal.
+fn session() -> String {
+    let moves = common::game_moves();
+    let mut res = HANDSHAKE.to_string();
+    for ply in 0..moves.len() {
+        res += "> position startpos";
+        if ply != 0 {
+            res += " moves ";
+            res += &moves[..ply].join(" ");
+        }
+        res += "\n> go wtime 300000 btime 300000 winc 2000 binc 2000\n";
+        for depth in 1..=MAX_DEPTH {
+            let nodes = 280 * depth * depth * depth;
+            let time = nodes / 900 + 1;
+            let score = (ply * 37 + depth * 13) % 120;
+            let pv = &moves[ply..moves.len().min(ply + depth)];
+            res += &format!(
+                "< info depth {} seldepth {} multipv 1 score cp {} nodes {} nps {} hashfull {} \
+                 tbhits 0 time {} pv {}\n",
+                depth,
+                depth + 1 + depth / 2,
+                score as i32 - 40,
+                nodes,
+                nodes * 1000 / time,
+                (depth * 41) % 1000,
+                time,
+                pv.join(" "),
+            );
+            if depth >= 10 {
+                res += &format!(
+                    "< info depth {} currmove {} currmovenumber 1\n",
+                    depth + 1,
+                    moves[ply],
+                );
+            }
+        }
+        res += "< bestmove ";
+        res += moves[ply];
+        if let Some(ponder) = moves.get(ply + 1) {
+            res += " ponder ";
+            res += ponder;
+        }
+        res += "\n";
+    }
+    res
+}
+
+fn lines_with_prefix(session: &str, prefix: char) -> Vec<&str> {
+    session
+        .lines()
+        .filter_map(|line| line.strip_prefix(prefix))
+        .collect()
+}
+
+fn bench_corpus(c: &mut Criterion) {
+    let session = session();
+    let commands = lines_with_prefix(&session, '>');
+    let messages = lines_with_prefix(&session, '<');
+
+    let mut group = c.benchmark_group("corpus");
+
+    group.throughput(Throughput::Elements(messages.len() as u64));
+    group.bench_function("messages", |b| {
+        b.iter(|| {
+            for line in &messages {
+                Message::parse_line(line, &mut Ignore).unwrap();
+            }
+        })
+    });
+
+    group.throughput(Throughput::Elements(commands.len() as u64));
+    group.bench_function("commands", |b| {
+        b.iter(|| {
+            for line in &commands {
+                Command::parse_line(line, &mut Ignore).unwrap();
+            }
+        })
+    });
+
+    group.throughput(Throughput::Bytes(session.len() as u64));
+    group.bench_function("transcript", |b| {
+        b.iter(|| parse::parse_transcript(&session, &mut Ignore))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_corpus);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use wurm::Ignore;
+
+use owlengine::uci::{
+    msg::Command,
+    parse::{Parse, PositionTracker},
+};
+
+mod common;
+
+fn game_lines() -> Vec<String> {
+    let mut line = "position startpos moves".to_string();
+    let mut result = Vec::new();
+    for mv in common::game_moves() {
+        line += " ";
+        line += mv;
+        result.push(line.clone());
+    }
+    result
+}
+
+fn bench_position(c: &mut Criterion) {
+    let lines = game_lines();
+
+    c.bench_function("position_full_game", |b| {
+        b.iter(|| {
+            for line in &lines {
+                Command::parse_line(line, &mut Ignore).unwrap();
+            }
+        })
+    });
+
+    c.bench_function("position_incremental_game", |b| {
+        b.iter(|| {
+            let mut tracker = PositionTracker::new();
+            for line in &lines {
+                tracker.update(line, &mut Ignore).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_position);
+criterion_main!(benches);
@@ -0,0 +1,505 @@
+use std::time::Duration;
+
+use owlchess::{
+    moves::{Move, UciMove},
+    Board, Color,
+};
+use thiserror::Error;
+use wurm::Warn;
+
+use crate::score::{BoundedAbsScore, BoundedRelScore};
+use crate::uci::{
+    msg::{Info, Message},
+    types::Permille,
+};
+
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct AnalysisStatus {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub time: Option<Duration>,
+    pub nodes: Option<u64>,
+    pub pv: Vec<UciMove>,
+    pub multipv: Option<u32>,
+    pub score: Option<BoundedRelScore>,
+    pub hashfull: Option<Permille>,
+    pub nps: Option<u64>,
+    pub cpuload: Option<Permille>,
+    pub tbhits: Option<u64>,
+    pub sbhits: Option<u64>,
+}
+
+#[inline]
+fn saturate_usize(val: u64) -> usize {
+    val.try_into().unwrap_or(usize::MAX)
+}
+
+impl AnalysisStatus {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, info: &Info) {
+        match info {
+            Info::Depth(val) => self.depth = Some(*val),
+            Info::SelDepth(val) => self.seldepth = Some(*val),
+            Info::Time(val) => self.time = Some(*val),
+            Info::Nodes(val) => self.nodes = Some(*val),
+            Info::Pv(moves) => self.pv = moves.clone(),
+            Info::MultiPv(val) => self.multipv = Some(*val),
+            Info::Score(val) => self.score = Some(*val),
+            Info::HashFull(val) => self.hashfull = Some(*val),
+            Info::Nps(val) => self.nps = Some(*val),
+            Info::CpuLoad(val) => self.cpuload = Some(*val),
+            Info::TbHits(val) => self.tbhits = Some(*val),
+            Info::SbHits(val) => self.sbhits = Some(*val),
+            Info::CurrMove(_)
+            | Info::CurrMoveNumber(_)
+            | Info::Refutation(_)
+            | Info::CurrLine { .. } => {}
+        }
+    }
+
+    /// Returns the score from White's perspective
+    ///
+    /// The engine reports the score relative to the side to move, so `side_to_move` must be taken
+    /// from the analyzed position, not from the position after the PV.
+    #[inline]
+    pub fn score_abs(&self, side_to_move: Color) -> Option<BoundedAbsScore> {
+        self.score.map(|score| score.abs_to(side_to_move))
+    }
+
+    /// Returns the node count as `usize`, saturating to `usize::MAX` if it doesn't fit (which may
+    /// happen on 32-bit targets)
+    #[inline]
+    pub fn nodes_usize(&self) -> Option<usize> {
+        self.nodes.map(saturate_usize)
+    }
+
+    /// Returns the nodes per second as `usize`, saturating to `usize::MAX` if it doesn't fit
+    /// (which may happen on 32-bit targets)
+    #[inline]
+    pub fn nps_usize(&self) -> Option<usize> {
+        self.nps.map(saturate_usize)
+    }
+}
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ConsistencyError {
+    #[error("depth decreased from {prev} to {cur}")]
+    DepthDecreased { prev: u32, cur: u32 },
+    #[error("time decreased from {prev:?} to {cur:?}")]
+    TimeDecreased { prev: Duration, cur: Duration },
+    #[error("nodes decreased from {prev} to {cur}")]
+    NodesDecreased { prev: u64, cur: u64 },
+}
+
+/// Checks that depth, time and nodes don't decrease within one search
+///
+/// Call [`SearchConsistencyChecker::reset`] when a new search starts.
+#[derive(Clone, Default, Debug)]
+pub struct SearchConsistencyChecker {
+    depth: Option<u32>,
+    time: Option<Duration>,
+    nodes: Option<u64>,
+}
+
+impl SearchConsistencyChecker {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn check(&mut self, status: &AnalysisStatus, warn: &mut impl Warn<ConsistencyError>) {
+        if let (Some(prev), Some(cur)) = (self.depth, status.depth) {
+            if cur < prev {
+                warn.warn(ConsistencyError::DepthDecreased { prev, cur });
+            }
+        }
+        if let (Some(prev), Some(cur)) = (self.time, status.time) {
+            if cur < prev {
+                warn.warn(ConsistencyError::TimeDecreased { prev, cur });
+            }
+        }
+        if let (Some(prev), Some(cur)) = (self.nodes, status.nodes) {
+            if cur < prev {
+                warn.warn(ConsistencyError::NodesDecreased { prev, cur });
+            }
+        }
+        self.depth = status.depth.or(self.depth);
+        self.time = status.time.or(self.time);
+        self.nodes = status.nodes.or(self.nodes);
+    }
+}
+
+/// Tracks how long the principal variation stays the same across successive updates
+///
+/// Only the updates for the main line are taken into account, i.e. the ones with `multipv` equal
+/// to 1 or absent, and with non-empty PV.
+#[derive(Clone, Default, Debug)]
+pub struct PvStabilityTracker {
+    pv: Vec<UciMove>,
+    same_move: usize,
+    same_pv: usize,
+}
+
+impl PvStabilityTracker {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn update(&mut self, status: &AnalysisStatus) {
+        if status.multipv.is_some_and(|m| m > 1) || status.pv.is_empty() {
+            return;
+        }
+        if self.pv.first() == status.pv.first() {
+            self.same_move += 1;
+        } else {
+            self.same_move = 1;
+        }
+        if self.pv == status.pv {
+            self.same_pv += 1;
+        } else {
+            self.same_pv = 1;
+            self.pv.clone_from(&status.pv);
+        }
+    }
+
+    /// Returns the number of consecutive updates which kept the same best move
+    #[inline]
+    pub fn stable_depth_count(&self) -> usize {
+        self.same_move
+    }
+
+    /// Returns the number of consecutive updates which kept the same full PV
+    #[inline]
+    pub fn stable_pv_count(&self) -> usize {
+        self.same_pv
+    }
+}
+
+/// Merges consecutive `info` messages which belong to the same search iteration
+///
+/// Some engines split one update across several `info` lines, e.g. send `depth` and `score` in
+/// the first one, and `pv` in the following one. The merger accumulates such lines into a single
+/// [`AnalysisStatus`]. The accumulated status is flushed when a line with different `depth` or
+/// `multipv` arrives, or on `bestmove`. Lines without `depth` or `multipv` are merged into the
+/// current status.
+#[derive(Clone, Default, Debug)]
+pub struct InfoMerger {
+    cur: Option<AnalysisStatus>,
+}
+
+impl InfoMerger {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds `msg` into the merger, returning the previous status if it got flushed
+    pub fn push(&mut self, msg: &Message) -> Option<AnalysisStatus> {
+        match msg {
+            Message::Info { info, .. } if !info.is_empty() => {
+                let mut res = None;
+                if let Some(cur) = &self.cur {
+                    let same_line = info.iter().all(|item| match item {
+                        Info::Depth(depth) => cur.depth.is_none_or(|d| d == *depth),
+                        Info::MultiPv(multipv) => cur.multipv.unwrap_or(1) == *multipv,
+                        _ => true,
+                    });
+                    if !same_line {
+                        res = self.cur.take();
+                    }
+                }
+                let cur = self.cur.get_or_insert_with(AnalysisStatus::new);
+                for item in info {
+                    cur.apply(item);
+                }
+                res
+            }
+            Message::BestMove { .. } => self.flush(),
+            _ => None,
+        }
+    }
+
+    /// Returns the accumulated status, if any, and starts over
+    #[inline]
+    pub fn flush(&mut self) -> Option<AnalysisStatus> {
+        self.cur.take()
+    }
+}
+
+/// Converts principal variations into legal moves, reusing the result of the previous conversion
+///
+/// During the live analysis, the engine sends the PVs from the same position, which often share
+/// a long prefix with the previous one. The resolver remembers the last resolved PV and only
+/// resolves the moves after the common prefix. If the position changes, the PV is resolved from
+/// scratch.
+#[derive(Clone, Debug, Default)]
+pub struct PvResolver {
+    board: Option<Board>,
+    tail: Option<Board>,
+    moves: Vec<Move>,
+    resolved_count: usize,
+}
+
+impl PvResolver {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves `pv` starting from `board`
+    ///
+    /// Returns the longest prefix of `pv` which consists of legal moves.
+    pub fn resolve(&mut self, board: &Board, pv: &[UciMove]) -> &[Move] {
+        if self.board.as_ref() != Some(board) {
+            self.board = Some(board.clone());
+            self.tail = None;
+            self.moves.clear();
+        }
+        let common = self
+            .moves
+            .iter()
+            .zip(pv)
+            .take_while(|(mv, uci)| mv.uci() == **uci)
+            .count();
+        if common < self.moves.len() || self.tail.is_none() {
+            self.moves.truncate(common);
+            let mut tail = board.clone();
+            for mv in &self.moves {
+                tail = tail.make_move(*mv).expect("resolved move must be legal");
+            }
+            self.tail = Some(tail);
+        }
+        let tail = self.tail.as_mut().unwrap();
+        for uci in &pv[common..] {
+            let Ok(mv) = uci.into_move(tail) else {
+                break;
+            };
+            let Ok(next) = tail.make_move(mv) else {
+                break;
+            };
+            *tail = next;
+            self.moves.push(mv);
+            self.resolved_count += 1;
+        }
+        &self.moves
+    }
+
+    /// Returns the total number of moves converted by [`PvResolver::resolve`]
+    ///
+    /// Moves taken from the previous PV are not counted, so this shows how much work the reuse
+    /// saves compared to the total length of the resolved PVs.
+    #[inline]
+    pub fn resolved_count(&self) -> usize {
+        self.resolved_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_usize_saturate() {
+        let mut status = AnalysisStatus::new();
+        assert_eq!(status.nodes_usize(), None);
+        assert_eq!(status.nps_usize(), None);
+
+        status.apply(&Info::Nodes(42));
+        status.apply(&Info::Nps(u64::MAX));
+        assert_eq!(status.nodes_usize(), Some(42));
+        assert_eq!(status.nps_usize(), Some(usize::MAX));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    fn test_usize_saturate_32bit() {
+        let mut status = AnalysisStatus::new();
+        status.apply(&Info::Nodes(1 << 32));
+        assert_eq!(status.nodes_usize(), Some(usize::MAX));
+    }
+
+    #[test]
+    fn test_score_abs() {
+        use crate::score::{AbsScore, Bound, RelScore};
+
+        let mut status = AnalysisStatus::new();
+        assert_eq!(status.score_abs(Color::Black), None);
+
+        status.score = Some(BoundedRelScore {
+            score: RelScore::Cp(-40),
+            bound: Bound::Lower,
+        });
+        assert_eq!(
+            status.score_abs(Color::Black),
+            Some(BoundedAbsScore {
+                score: AbsScore::Cp(40),
+                bound: Bound::Upper,
+            })
+        );
+        assert_eq!(
+            status.score_abs(Color::White),
+            Some(BoundedAbsScore {
+                score: AbsScore::Cp(-40),
+                bound: Bound::Lower,
+            })
+        );
+    }
+
+    #[test]
+    fn test_tbhits_sbhits() {
+        use crate::uci::parse::Parse;
+
+        let mut status = AnalysisStatus {
+            depth: Some(12),
+            ..Default::default()
+        };
+        let msg = Message::parse_line("info tbhits 1000 sbhits 5", &mut CollectAll::default());
+        let Some(Message::Info { info, .. }) = msg else {
+            panic!("expected info, got {:?}", msg);
+        };
+        for item in &info {
+            status.apply(item);
+        }
+        assert_eq!(status.tbhits, Some(1000));
+        assert_eq!(status.sbhits, Some(5));
+        assert_eq!(status.depth, Some(12));
+    }
+
+    #[test]
+    fn test_consistency() {
+        let status = |depth, nodes| AnalysisStatus {
+            depth: Some(depth),
+            nodes: Some(nodes),
+            ..Default::default()
+        };
+        let mut checker = SearchConsistencyChecker::new();
+        let mut warn = CollectAll::default();
+        checker.check(&status(10, 1000), &mut warn);
+        checker.check(&status(11, 5000), &mut warn);
+        checker.check(&status(11, 5000), &mut warn);
+        assert!(warn.0.is_empty());
+
+        checker.check(&status(9, 6000), &mut warn);
+        assert_eq!(
+            warn.0,
+            vec![ConsistencyError::DepthDecreased { prev: 11, cur: 9 }]
+        );
+
+        checker.reset();
+        checker.check(&status(1, 10), &mut warn);
+        assert_eq!(warn.0.len(), 1);
+    }
+
+    #[test]
+    fn test_pv_stability() {
+        let status = |depth, pv: &[&str]| AnalysisStatus {
+            depth: Some(depth),
+            pv: pv.iter().map(|mv| mv.parse().unwrap()).collect(),
+            ..Default::default()
+        };
+        let mut tracker = PvStabilityTracker::new();
+        assert_eq!(tracker.stable_depth_count(), 0);
+
+        tracker.update(&status(1, &["e2e4"]));
+        tracker.update(&status(2, &["d2d4", "d7d5"]));
+        assert_eq!(tracker.stable_depth_count(), 1);
+        assert_eq!(tracker.stable_pv_count(), 1);
+
+        tracker.update(&status(3, &["d2d4", "g8f6"]));
+        tracker.update(&status(4, &["d2d4", "g8f6"]));
+        tracker.update(&status(4, &[]));
+        tracker.update(&AnalysisStatus {
+            multipv: Some(2),
+            ..status(4, &["e2e4"])
+        });
+        tracker.update(&status(5, &["d2d4", "g8f6"]));
+        assert_eq!(tracker.stable_depth_count(), 4);
+        assert_eq!(tracker.stable_pv_count(), 3);
+
+        tracker.update(&status(6, &["c2c4"]));
+        assert_eq!(tracker.stable_depth_count(), 1);
+        assert_eq!(tracker.stable_pv_count(), 1);
+    }
+
+    #[test]
+    fn test_info_merger() {
+        use crate::uci::parse::Parse;
+
+        let mut merger = InfoMerger::new();
+        let mut flushed = Vec::new();
+        for line in [
+            "info depth 5 score cp 20 nodes 1000",
+            "info pv e2e4 e7e5",
+            "info string hello",
+            "info depth 6 score cp 25",
+            "info pv d2d4",
+            "bestmove d2d4",
+        ] {
+            let msg = Message::parse_line(line, &mut CollectAll::default()).unwrap();
+            flushed.extend(merger.push(&msg));
+        }
+        assert!(merger.flush().is_none());
+
+        assert_eq!(flushed.len(), 2);
+        assert_eq!(flushed[0].depth, Some(5));
+        assert_eq!(flushed[0].nodes, Some(1000));
+        assert_eq!(flushed[0].pv.len(), 2);
+        assert_eq!(flushed[1].depth, Some(6));
+        assert_eq!(flushed[1].pv, vec!["d2d4".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_pv_resolver() {
+        let pv = |moves: &[&str]| -> Vec<UciMove> {
+            moves.iter().map(|mv| mv.parse().unwrap()).collect()
+        };
+        // The reused result must be the same as the one resolved from scratch
+        let resolve = |resolver: &mut PvResolver, board: &Board, moves: &[&str]| -> usize {
+            let fresh = PvResolver::new().resolve(board, &pv(moves)).to_vec();
+            let cached = resolver.resolve(board, &pv(moves));
+            assert_eq!(cached, fresh.as_slice());
+            cached.len()
+        };
+        let board = Board::initial();
+        let mut resolver = PvResolver::new();
+
+        assert_eq!(resolve(&mut resolver, &board, &["e2e4", "e7e5"]), 2);
+        assert_eq!(resolver.resolved_count(), 2);
+
+        assert_eq!(resolve(&mut resolver, &board, &["e2e4", "e7e5", "g1f3"]), 3);
+        assert_eq!(resolver.resolved_count(), 3);
+
+        assert_eq!(resolve(&mut resolver, &board, &["e2e4", "c7c5"]), 2);
+        assert_eq!(resolver.resolved_count(), 4);
+
+        assert_eq!(
+            resolve(&mut resolver, &board, &["e2e4", "c7c5", "e4e6", "g1f3"]),
+            2
+        );
+        assert_eq!(resolver.resolved_count(), 4);
+
+        let other = board
+            .make_move(Move::from_uci("d2d4", &board).unwrap())
+            .unwrap();
+        assert_eq!(resolve(&mut resolver, &other, &["e2e4"]), 0);
+        assert_eq!(resolve(&mut resolver, &other, &["d7d5"]), 1);
+        assert_eq!(resolver.resolved_count(), 5);
+    }
+}
@@ -0,0 +1,146 @@
+//! Minimal C API for parsing UCI commands
+//!
+//! To get a shared library, build the crate with `capi` feature as a `cdylib`, e.g. via
+//! `cargo rustc --release --features capi --crate-type cdylib`.
+
+use std::{
+    ffi::{c_char, CStr},
+    ptr,
+};
+
+use wurm::Ignore;
+
+use crate::uci::{msg::Command, parse::Parse};
+
+/// Opaque handle to a parsed command
+pub struct OwlCommand(Command);
+
+/// Kind of the parsed command
+///
+/// [`OwlCommandKind::None`] indicates that no command was parsed.
+#[repr(i32)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum OwlCommandKind {
+    None = -1,
+    Uci = 0,
+    Debug = 1,
+    IsReady = 2,
+    SetOption = 3,
+    Register = 4,
+    UciNewGame = 5,
+    Position = 6,
+    Go = 7,
+    Stop = 8,
+    PonderHit = 9,
+    Quit = 10,
+}
+
+impl OwlCommandKind {
+    fn of(cmd: &Command) -> Self {
+        match cmd {
+            Command::Uci => Self::Uci,
+            Command::Debug(_) => Self::Debug,
+            Command::IsReady => Self::IsReady,
+            Command::SetOption { .. } => Self::SetOption,
+            Command::Register(_) => Self::Register,
+            Command::UciNewGame => Self::UciNewGame,
+            Command::Position { .. } => Self::Position,
+            Command::Go(_) => Self::Go,
+            Command::Stop => Self::Stop,
+            Command::PonderHit => Self::PonderHit,
+            Command::Quit => Self::Quit,
+        }
+    }
+}
+
+/// Parses a command from a NUL-terminated UTF-8 string `line`
+///
+/// Returns the kind of the command as [`OwlCommandKind`], or `-1` if `line` is null, is not a
+/// valid UTF-8 or doesn't contain a command. Warnings are ignored. If `out` is not null and the
+/// command was parsed, a handle to it is stored into `*out`, which must be freed later via
+/// [`owl_command_free`]. Otherwise, null is stored into `*out`.
+///
+/// # Safety
+///
+/// `line` must be either null or a valid NUL-terminated string. `out` must be either null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn owl_parse_command(line: *const c_char, out: *mut *mut OwlCommand) -> i32 {
+    if !out.is_null() {
+        *out = ptr::null_mut();
+    }
+    if line.is_null() {
+        return OwlCommandKind::None as i32;
+    }
+    let Ok(line) = CStr::from_ptr(line).to_str() else {
+        return OwlCommandKind::None as i32;
+    };
+    let Some(cmd) = Command::parse_line(line, &mut Ignore) else {
+        return OwlCommandKind::None as i32;
+    };
+    let kind = OwlCommandKind::of(&cmd);
+    if !out.is_null() {
+        *out = Box::into_raw(Box::new(OwlCommand(cmd)));
+    }
+    kind as i32
+}
+
+/// Returns the kind of the command `cmd` as [`OwlCommandKind`], or `-1` if `cmd` is null
+///
+/// # Safety
+///
+/// `cmd` must be either null or a handle returned from [`owl_parse_command`] which was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn owl_command_kind(cmd: *const OwlCommand) -> i32 {
+    match cmd.as_ref() {
+        Some(cmd) => OwlCommandKind::of(&cmd.0) as i32,
+        None => OwlCommandKind::None as i32,
+    }
+}
+
+/// Frees the command `cmd`. Does nothing if `cmd` is null
+///
+/// # Safety
+///
+/// `cmd` must be either null or a handle returned from [`owl_parse_command`] which was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn owl_command_free(cmd: *mut OwlCommand) {
+    if !cmd.is_null() {
+        drop(Box::from_raw(cmd));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_parse_command() {
+        let line = CString::new("go depth 10").unwrap();
+        let mut cmd = ptr::null_mut();
+        let kind = unsafe { owl_parse_command(line.as_ptr(), &mut cmd) };
+        assert_eq!(kind, OwlCommandKind::Go as i32);
+        assert!(!cmd.is_null());
+        assert_eq!(unsafe { owl_command_kind(cmd) }, OwlCommandKind::Go as i32);
+        unsafe { owl_command_free(cmd) };
+
+        let line = CString::new("isready").unwrap();
+        let kind = unsafe { owl_parse_command(line.as_ptr(), ptr::null_mut()) };
+        assert_eq!(kind, OwlCommandKind::IsReady as i32);
+
+        let line = CString::new("").unwrap();
+        let mut cmd = ptr::null_mut();
+        let kind = unsafe { owl_parse_command(line.as_ptr(), &mut cmd) };
+        assert_eq!(kind, OwlCommandKind::None as i32);
+        assert!(cmd.is_null());
+
+        let kind = unsafe { owl_parse_command(ptr::null(), &mut cmd) };
+        assert_eq!(kind, OwlCommandKind::None as i32);
+        assert_eq!(
+            unsafe { owl_command_kind(ptr::null()) },
+            OwlCommandKind::None as i32
+        );
+        unsafe { owl_command_free(ptr::null_mut()) };
+    }
+}
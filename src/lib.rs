@@ -1,2 +1,6 @@
+pub mod analysis;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod score;
 pub mod uci;
+pub mod util;
@@ -1,6 +1,9 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::{Ordering, Reverse},
+    ops::Neg,
+};
 
-use owlchess::Color;
+use owlchess::{Color, RawBoard};
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub enum Bound {
@@ -26,6 +29,14 @@ impl Bound {
         }
     }
 
+    fn optimism(self) -> u8 {
+        match self {
+            Self::Upper => 0,
+            Self::Exact => 1,
+            Self::Lower => 2,
+        }
+    }
+
     fn rel_side(self, side: Color) -> Self {
         match side {
             Color::White => self,
@@ -70,6 +81,36 @@ impl RelScore {
         }
     }
 
+    /// Largest mate distance distinguished by [`RelScore::to_gui_cp`]
+    ///
+    /// Longer mates are shown as mate in `MAX_GUI_MATE` moves.
+    pub const MAX_GUI_MATE: u32 = 1000;
+
+    /// Converts the score into centipawns for display in GUIs
+    ///
+    /// Mate in `moves` is mapped to `mate_cp_base - moves` (negated if the side loses), so faster
+    /// mates yield larger magnitudes. Mate distances are capped at [`RelScore::MAX_GUI_MATE`], and
+    /// centipawn scores are clamped to `[-limit, limit]`, where
+    /// `limit = mate_cp_base - MAX_GUI_MATE - 1`. So, any mate is always ranked above any
+    /// centipawn score.
+    #[inline]
+    pub fn to_gui_cp(self, mate_cp_base: i32) -> i32 {
+        let max_mate = Self::MAX_GUI_MATE as i32;
+        let cp_limit = mate_cp_base.saturating_sub(max_mate + 1).max(0);
+        match self {
+            Self::Cp(val) => val.clamp(-cp_limit, cp_limit),
+            Self::Mate { moves, win } => {
+                let moves = moves.min(Self::MAX_GUI_MATE) as i32;
+                let val = mate_cp_base.saturating_sub(moves).max(cp_limit + 1);
+                if win {
+                    val
+                } else {
+                    -val
+                }
+            }
+        }
+    }
+
     fn as_cmp_tuple(&self) -> (i32, i64) {
         match *self {
             Self::Cp(val) => (0, val as i64),
@@ -93,6 +134,16 @@ impl Ord for RelScore {
     }
 }
 
+/// Negation flips the perspective, i.e. it is the same as [`RelScore::inv`]
+impl Neg for RelScore {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.inv()
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub enum AbsScore {
     Cp(i32),
@@ -136,6 +187,16 @@ impl Ord for AbsScore {
     }
 }
 
+/// Negation flips the perspective, i.e. the score is inverted as seen by White
+impl Neg for AbsScore {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.rel_to(Color::White).inv().abs_to(Color::White)
+    }
+}
+
 #[derive(Copy, Clone, Default, PartialEq, Eq, Debug, Hash)]
 pub struct BoundedRelScore {
     pub score: RelScore,
@@ -158,6 +219,24 @@ impl BoundedRelScore {
             bound: self.bound.rel_side(side),
         }
     }
+
+    /// Converts the score into centipawns for display in GUIs, ignoring the bound
+    ///
+    /// See [`RelScore::to_gui_cp`] for details.
+    #[inline]
+    pub fn to_gui_cp(&self, mate_cp_base: i32) -> i32 {
+        self.score.to_gui_cp(mate_cp_base)
+    }
+}
+
+/// Negation flips the perspective, i.e. it is the same as [`BoundedRelScore::inv`]
+impl Neg for BoundedRelScore {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.inv()
+    }
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq, Debug, Hash)]
@@ -176,6 +255,67 @@ impl BoundedAbsScore {
     }
 }
 
+/// Negation flips the perspective, i.e. the score is inverted as seen by White
+impl Neg for BoundedAbsScore {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.rel_to(Color::White).inv().abs_to(Color::White)
+    }
+}
+
+/// Converts the score reported by the engine for `board` into the absolute one, using the side to
+/// move from `board`
+#[inline]
+pub fn score_abs_from_position(score: BoundedRelScore, board: &RawBoard) -> BoundedAbsScore {
+    score.abs_to(board.side)
+}
+
+/// Converts the score reported by the engine into the one shown to the user, i.e. from White's
+/// perspective, given the side to move
+///
+/// Same as [`BoundedRelScore::abs_to`]: for Black, centipawns change sign, and lower and upper
+/// bounds swap.
+#[inline]
+pub fn display_score(score: BoundedRelScore, side_to_move: Color) -> BoundedAbsScore {
+    score.abs_to(side_to_move)
+}
+
+/// Formats the score as PGN comment annotation, like `[%eval 0.31]` or `[%eval #-3]`
+///
+/// Centipawns are converted to pawns with two digits after the decimal point. Mate is denoted as
+/// `#n` if White mates and `#-n` if Black mates. The bound is dropped, as the annotation cannot
+/// express it.
+pub fn eval_comment(score: BoundedAbsScore) -> String {
+    match score.score {
+        AbsScore::Cp(val) => {
+            let sign = if val < 0 { "-" } else { "" };
+            let val = val.unsigned_abs();
+            format!("[%eval {}{}.{:02}]", sign, val / 100, val % 100)
+        }
+        AbsScore::Mate { moves, winner } => {
+            let sign = match winner {
+                Color::White => "",
+                Color::Black => "-",
+            };
+            format!("[%eval #{}{}]", sign, moves)
+        }
+    }
+}
+
+/// Returns the multipv number of the line with the best score
+///
+/// The scores are compared optimistically: for equal values, a lower bound is considered better
+/// than the exact score, and the exact score is better than an upper bound. Ties are broken in
+/// favor of the lowest multipv number.
+pub fn best_multipv(scores: &[(u32, BoundedRelScore)]) -> Option<u32> {
+    scores
+        .iter()
+        .max_by_key(|(multipv, score)| (score.score, score.bound.optimism(), Reverse(*multipv)))
+        .map(|(multipv, _)| *multipv)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,6 +361,239 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_abs_from_position() {
+        let board =
+            RawBoard::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+                .unwrap();
+        let score = BoundedRelScore {
+            score: RelScore::Cp(-40),
+            bound: Bound::Lower,
+        };
+        assert_eq!(
+            score_abs_from_position(score, &board),
+            BoundedAbsScore {
+                score: AbsScore::Cp(40),
+                bound: Bound::Upper,
+            }
+        );
+        assert_eq!(
+            score_abs_from_position(score, &RawBoard::initial()),
+            BoundedAbsScore {
+                score: AbsScore::Cp(-40),
+                bound: Bound::Lower,
+            }
+        );
+    }
+
+    #[test]
+    fn test_display_score() {
+        let rel_scores = [
+            (RelScore::Cp(30), AbsScore::Cp(30), AbsScore::Cp(-30)),
+            (RelScore::Cp(-45), AbsScore::Cp(-45), AbsScore::Cp(45)),
+            (
+                RelScore::Mate {
+                    moves: 3,
+                    win: true,
+                },
+                AbsScore::Mate {
+                    moves: 3,
+                    winner: Color::White,
+                },
+                AbsScore::Mate {
+                    moves: 3,
+                    winner: Color::Black,
+                },
+            ),
+            (
+                RelScore::Mate {
+                    moves: 5,
+                    win: false,
+                },
+                AbsScore::Mate {
+                    moves: 5,
+                    winner: Color::Black,
+                },
+                AbsScore::Mate {
+                    moves: 5,
+                    winner: Color::White,
+                },
+            ),
+        ];
+        let bounds = [
+            (Bound::Exact, Bound::Exact, Bound::Exact),
+            (Bound::Lower, Bound::Lower, Bound::Upper),
+            (Bound::Upper, Bound::Upper, Bound::Lower),
+        ];
+        for (rel, white_abs, black_abs) in rel_scores {
+            for (bound, white_bound, black_bound) in bounds {
+                let score = BoundedRelScore { score: rel, bound };
+                assert_eq!(
+                    display_score(score, Color::White),
+                    BoundedAbsScore {
+                        score: white_abs,
+                        bound: white_bound,
+                    }
+                );
+                assert_eq!(
+                    display_score(score, Color::Black),
+                    BoundedAbsScore {
+                        score: black_abs,
+                        bound: black_bound,
+                    }
+                );
+                for side in [Color::White, Color::Black] {
+                    assert_eq!(display_score(score, side).rel_to(side), score);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_comment() {
+        let comment = |score, bound| eval_comment(BoundedAbsScore { score, bound });
+        assert_eq!(comment(AbsScore::Cp(31), Bound::Exact), "[%eval 0.31]");
+        assert_eq!(comment(AbsScore::Cp(-5), Bound::Exact), "[%eval -0.05]");
+        assert_eq!(comment(AbsScore::Cp(0), Bound::Exact), "[%eval 0.00]");
+        assert_eq!(comment(AbsScore::Cp(-250), Bound::Lower), "[%eval -2.50]");
+        assert_eq!(comment(AbsScore::Cp(1234), Bound::Upper), "[%eval 12.34]");
+        assert_eq!(
+            comment(AbsScore::Cp(i32::MIN), Bound::Exact),
+            "[%eval -21474836.48]"
+        );
+        assert_eq!(
+            comment(
+                AbsScore::Mate {
+                    moves: 3,
+                    winner: Color::White
+                },
+                Bound::Exact
+            ),
+            "[%eval #3]"
+        );
+        assert_eq!(
+            comment(
+                AbsScore::Mate {
+                    moves: 5,
+                    winner: Color::Black
+                },
+                Bound::Lower
+            ),
+            "[%eval #-5]"
+        );
+    }
+
+    #[test]
+    fn test_best_multipv() {
+        let score = |score, bound| BoundedRelScore { score, bound };
+        assert_eq!(best_multipv(&[]), None);
+        assert_eq!(
+            best_multipv(&[
+                (1, score(RelScore::Cp(30), Bound::Exact)),
+                (2, score(RelScore::Cp(45), Bound::Exact)),
+                (3, score(RelScore::Cp(-10), Bound::Lower)),
+            ]),
+            Some(2)
+        );
+        assert_eq!(
+            best_multipv(&[
+                (1, score(RelScore::Cp(30), Bound::Upper)),
+                (2, score(RelScore::Cp(30), Bound::Exact)),
+                (3, score(RelScore::Cp(30), Bound::Lower)),
+            ]),
+            Some(3)
+        );
+        assert_eq!(
+            best_multipv(&[
+                (3, score(RelScore::Cp(30), Bound::Exact)),
+                (1, score(RelScore::Cp(30), Bound::Exact)),
+                (2, score(RelScore::Cp(30), Bound::Exact)),
+            ]),
+            Some(1)
+        );
+        assert_eq!(
+            best_multipv(&[
+                (1, score(RelScore::Cp(900), Bound::Exact)),
+                (
+                    2,
+                    score(
+                        RelScore::Mate {
+                            moves: 7,
+                            win: true
+                        },
+                        Bound::Exact
+                    )
+                ),
+            ]),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_gui_cp() {
+        let score = |score| BoundedRelScore {
+            score,
+            bound: Bound::Exact,
+        };
+        let mate = |moves, win| score(RelScore::Mate { moves, win });
+        assert_eq!(score(RelScore::Cp(31)).to_gui_cp(30000), 31);
+        assert_eq!(score(RelScore::Cp(-31)).to_gui_cp(30000), -31);
+        assert_eq!(score(RelScore::Cp(50000)).to_gui_cp(30000), 28999);
+        assert_eq!(score(RelScore::Cp(-50000)).to_gui_cp(30000), -28999);
+        assert_eq!(mate(3, true).to_gui_cp(30000), 29997);
+        assert_eq!(mate(3, false).to_gui_cp(30000), -29997);
+        assert!(mate(1, true).to_gui_cp(30000) > mate(5, true).to_gui_cp(30000));
+        assert!(mate(1, false).to_gui_cp(30000) < mate(5, false).to_gui_cp(30000));
+        assert!(mate(40, true).to_gui_cp(30000) > score(RelScore::Cp(2000)).to_gui_cp(30000));
+        assert!(mate(3, true).to_gui_cp(30000) > score(RelScore::Cp(29999)).to_gui_cp(30000));
+        assert!(mate(0, true).to_gui_cp(30000) > score(RelScore::Cp(50000)).to_gui_cp(30000));
+        assert_eq!(mate(u32::MAX, true).to_gui_cp(30000), 29000);
+        assert_eq!(mate(u32::MAX, false).to_gui_cp(30000), -29000);
+        assert!(
+            mate(u32::MAX, true).to_gui_cp(30000) > score(RelScore::Cp(i32::MAX)).to_gui_cp(30000)
+        );
+
+        // Even with a tiny base, mates stay above centipawns
+        assert_eq!(score(RelScore::Cp(500)).to_gui_cp(100), 0);
+        assert_eq!(mate(3, true).to_gui_cp(100), 97);
+        assert_eq!(mate(200, true).to_gui_cp(100), 1);
+        assert_eq!(mate(200, false).to_gui_cp(100), -1);
+    }
+
+    #[test]
+    fn test_neg() {
+        for rel in [
+            RelScore::Cp(-100),
+            RelScore::Cp(0),
+            RelScore::Cp(280),
+            RelScore::Mate {
+                moves: 2,
+                win: true,
+            },
+            RelScore::Mate {
+                moves: 3,
+                win: false,
+            },
+        ] {
+            assert_eq!(-rel, rel.inv());
+            assert_eq!(-(-rel), rel);
+
+            let abs = rel.abs_to(Color::White);
+            assert_eq!(-abs, rel.inv().abs_to(Color::White));
+            assert_eq!(-(-abs), abs);
+
+            for bound in [Bound::Lower, Bound::Upper, Bound::Exact] {
+                let bounded = BoundedRelScore { score: rel, bound };
+                assert_eq!(-bounded, bounded.inv());
+                assert_eq!(-(-bounded), bounded);
+
+                let abs = bounded.abs_to(Color::Black);
+                assert_eq!(-abs, bounded.inv().abs_to(Color::Black));
+                assert_eq!(-(-abs), abs);
+            }
+        }
+    }
+
     #[test]
     fn test_sort_rel() {
         let mut src = [
@@ -1,5 +1,6 @@
 pub mod msg;
 pub mod parse;
+pub mod sink;
 pub mod str;
 pub mod token;
 pub mod types;
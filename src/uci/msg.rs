@@ -31,11 +31,66 @@ pub struct Go {
     pub movetime: Option<Duration>,
 }
 
+impl Go {
+    /// Returns a stable compact description of the limits, suitable for logs and test assertions
+    ///
+    /// Examples are `infinite`, `clock w=1000 b=1000 winc=0 binc=0` and `depth=20`. Times are
+    /// given in milliseconds. If no limits are set, returns `none`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(moves) = &self.searchmoves {
+            let moves: Vec<_> = moves.iter().map(|mv| mv.to_string()).collect();
+            parts.push(format!("searchmoves={}", moves.join(",")));
+        }
+        if self.ponder.is_some() {
+            parts.push("ponder".to_string());
+        }
+        if self.infinite.is_some() {
+            parts.push("infinite".to_string());
+        }
+        let clock = [
+            ("w", self.wtime),
+            ("b", self.btime),
+            ("winc", self.winc),
+            ("binc", self.binc),
+        ];
+        if clock.iter().any(|(_, val)| val.is_some()) || self.movestogo.is_some() {
+            parts.push("clock".to_string());
+            for (name, val) in clock {
+                if let Some(val) = val {
+                    parts.push(format!("{}={}", name, val.as_millis()));
+                }
+            }
+            if let Some(val) = self.movestogo {
+                parts.push(format!("movestogo={}", val));
+            }
+        }
+        if let Some(val) = self.depth {
+            parts.push(format!("depth={}", val));
+        }
+        if let Some(val) = self.nodes {
+            parts.push(format!("nodes={}", val));
+        }
+        if let Some(val) = self.mate {
+            parts.push(format!("mate={}", val));
+        }
+        if let Some(val) = self.movetime {
+            parts.push(format!("movetime={}", val.as_millis()));
+        }
+        if parts.is_empty() {
+            return "none".to_string();
+        }
+        parts.join(" ")
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Command {
     Uci,
     Debug(bool),
     IsReady,
+    /// Formatted as `setoption name <name> [value <value>]`, in this exact order, with single
+    /// spaces between tokens
     SetOption {
         name: OptName,
         value: Option<UciString>,
@@ -52,6 +107,26 @@ pub enum Command {
     Quit,
 }
 
+impl Command {
+    /// If both `self` and `prev` are `position` commands, and `self` extends `prev` by appending
+    /// moves, returns the appended moves
+    ///
+    /// Returns `None` if the commands are not `position`, if their starting positions differ, or
+    /// if the moves of `prev` are not a prefix of the moves of `self`.
+    pub fn is_continuation_of(&self, prev: &Command) -> Option<&[UciMove]> {
+        match (self, prev) {
+            (
+                Command::Position { startpos, moves },
+                Command::Position {
+                    startpos: prev_startpos,
+                    moves: prev_moves,
+                },
+            ) if startpos == prev_startpos => moves.strip_prefix(prev_moves.as_slice()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Id {
     Name(UciString),
@@ -78,6 +153,27 @@ pub enum Info {
     CurrLine { cpu_num: u32, moves: Vec<UciMove> },
 }
 
+impl Info {
+    /// For [`Info::Refutation`], returns the move being refuted, i.e. the first one
+    #[inline]
+    pub fn refuted_move(&self) -> Option<UciMove> {
+        match self {
+            Info::Refutation(moves) => moves.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// For [`Info::Refutation`], returns the line which refutes [`Info::refuted_move`], i.e. all
+    /// the moves except the first one. Otherwise, returns an empty slice
+    #[inline]
+    pub fn refutation_line(&self) -> &[UciMove] {
+        match self {
+            Info::Refutation(moves) => moves.get(1..).unwrap_or(&[]),
+            _ => &[],
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum OptBody {
     Check(bool),
@@ -114,3 +210,229 @@ pub enum Message {
         body: OptBody,
     },
 }
+
+/// Direction in which a line is sent
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    /// From GUI to engine, i.e. a [`Command`]
+    ToEngine,
+    /// From engine to GUI, i.e. a [`Message`]
+    FromEngine,
+}
+
+/// Either a command sent to the engine or a message received from it
+///
+/// Displayed in the same form as in transcripts, i.e. commands are prefixed with `>` and messages
+/// are prefixed with `<`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum UciEvent {
+    ToEngine(Command),
+    FromEngine(Message),
+}
+
+impl UciEvent {
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        match self {
+            Self::ToEngine(_) => Direction::ToEngine,
+            Self::FromEngine(_) => Direction::FromEngine,
+        }
+    }
+}
+
+impl Message {
+    /// Returns `true` if the message is `info` without any items and without a string
+    #[inline]
+    pub fn is_empty_info(&self) -> bool {
+        matches!(self, Message::Info { info, string: None } if info.is_empty())
+    }
+}
+
+/// Drops empty `info` messages (see [`Message::is_empty_info`]) from `iter`
+#[inline]
+pub fn skip_empty_info<I>(iter: I) -> impl Iterator<Item = Message>
+where
+    I: IntoIterator<Item = Message>,
+{
+    iter.into_iter().filter(|msg| !msg.is_empty_info())
+}
+
+/// Finds the `option` declaration in `opts` which the `setoption` command `cmd` refers to
+///
+/// Option names are compared case-insensitively, as [`OptName`] does. Returns `None` if `cmd` is
+/// not `setoption` or no such option is declared. Messages other than `option` are skipped.
+pub fn resolve_set_option<'a>(cmd: &Command, opts: &'a [Message]) -> Option<&'a Message> {
+    let Command::SetOption { name, .. } = cmd else {
+        return None;
+    };
+    opts.iter()
+        .find(|msg| matches!(msg, Message::Option { name: opt_name, .. } if opt_name == name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(startpos: RawBoard, moves: &[&str]) -> Command {
+        Command::Position {
+            startpos,
+            moves: moves.iter().map(|mv| mv.parse().unwrap()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_continuation() {
+        let prev = position(RawBoard::initial(), &["e2e4", "e7e5"]);
+        let next = position(RawBoard::initial(), &["e2e4", "e7e5", "g1f3"]);
+        assert_eq!(
+            next.is_continuation_of(&prev),
+            Some(&["g1f3".parse().unwrap()][..])
+        );
+        assert_eq!(prev.is_continuation_of(&prev), Some(&[][..]));
+        assert_eq!(prev.is_continuation_of(&next), None);
+    }
+
+    #[test]
+    fn test_reset() {
+        let prev = position(RawBoard::initial(), &["e2e4", "e7e5"]);
+        let other_moves = position(RawBoard::initial(), &["d2d4", "d7d5", "c2c4"]);
+        assert_eq!(other_moves.is_continuation_of(&prev), None);
+
+        let board = RawBoard::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap();
+        let other_board = position(board, &["e2e4", "e7e5", "g1f3"]);
+        assert_eq!(other_board.is_continuation_of(&prev), None);
+
+        assert_eq!(Command::UciNewGame.is_continuation_of(&prev), None);
+    }
+
+    #[test]
+    fn test_empty_info() {
+        let empty = Message::Info {
+            info: Vec::new(),
+            string: None,
+        };
+        let depth = Message::Info {
+            info: vec![Info::Depth(3)],
+            string: None,
+        };
+        let string = Message::Info {
+            info: Vec::new(),
+            string: Some(UciString::new()),
+        };
+        assert!(empty.is_empty_info());
+        assert!(!depth.is_empty_info());
+        assert!(!string.is_empty_info());
+        assert!(!Message::ReadyOk.is_empty_info());
+
+        let msgs = vec![empty.clone(), depth.clone(), empty, Message::ReadyOk];
+        assert_eq!(
+            skip_empty_info(msgs).collect::<Vec<_>>(),
+            vec![depth, Message::ReadyOk]
+        );
+    }
+
+    #[test]
+    fn test_go_describe() {
+        assert_eq!(Go::default().describe(), "none");
+        assert_eq!(
+            Go {
+                infinite: Some(()),
+                ..Default::default()
+            }
+            .describe(),
+            "infinite"
+        );
+        assert_eq!(
+            Go {
+                wtime: Some(Duration::from_millis(1000)),
+                btime: Some(Duration::from_millis(1000)),
+                winc: Some(Duration::ZERO),
+                binc: Some(Duration::ZERO),
+                ..Default::default()
+            }
+            .describe(),
+            "clock w=1000 b=1000 winc=0 binc=0"
+        );
+        assert_eq!(
+            Go {
+                wtime: Some(Duration::from_millis(60000)),
+                btime: Some(Duration::from_millis(58000)),
+                movestogo: NonZeroU64::new(40),
+                ponder: Some(()),
+                ..Default::default()
+            }
+            .describe(),
+            "ponder clock w=60000 b=58000 movestogo=40"
+        );
+        assert_eq!(
+            Go {
+                depth: Some(20),
+                ..Default::default()
+            }
+            .describe(),
+            "depth=20"
+        );
+        assert_eq!(
+            Go {
+                searchmoves: Some(vec!["e2e4".parse().unwrap(), "d2d4".parse().unwrap()]),
+                nodes: Some(100000),
+                movetime: Some(Duration::from_secs(5)),
+                ..Default::default()
+            }
+            .describe(),
+            "searchmoves=e2e4,d2d4 nodes=100000 movetime=5000"
+        );
+    }
+
+    #[test]
+    fn test_refutation() {
+        let moves =
+            |s: &[&str]| -> Vec<UciMove> { s.iter().map(|mv| mv.parse().unwrap()).collect() };
+        let info = Info::Refutation(moves(&["e2e4", "e7e5", "g1f3"]));
+        assert_eq!(info.refuted_move(), Some("e2e4".parse().unwrap()));
+        assert_eq!(info.refutation_line(), moves(&["e7e5", "g1f3"]));
+
+        let info = Info::Refutation(moves(&["e2e4"]));
+        assert_eq!(info.refuted_move(), Some("e2e4".parse().unwrap()));
+        assert!(info.refutation_line().is_empty());
+
+        let info = Info::Refutation(Vec::new());
+        assert_eq!(info.refuted_move(), None);
+        assert!(info.refutation_line().is_empty());
+
+        let info = Info::Pv(moves(&["e2e4", "e7e5"]));
+        assert_eq!(info.refuted_move(), None);
+        assert!(info.refutation_line().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_set_option() {
+        let opts = [
+            Message::UciOk,
+            Message::Option {
+                name: "Hash".parse().unwrap(),
+                body: OptBody::Spin {
+                    default: 16,
+                    min: 1,
+                    max: 1024,
+                },
+            },
+            Message::Option {
+                name: "Ponder".parse().unwrap(),
+                body: OptBody::Check(false),
+            },
+        ];
+        let cmd = Command::SetOption {
+            name: "hash".parse().unwrap(),
+            value: Some("64".into()),
+        };
+        assert_eq!(resolve_set_option(&cmd, &opts), Some(&opts[1]));
+
+        let cmd = Command::SetOption {
+            name: "Threads".parse().unwrap(),
+            value: Some("4".into()),
+        };
+        assert_eq!(resolve_set_option(&cmd, &opts), None);
+        assert_eq!(resolve_set_option(&Command::IsReady, &opts), None);
+    }
+}
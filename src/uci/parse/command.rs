@@ -1,5 +1,8 @@
+use std::ops::Range;
+
 use owlchess::board::{RawBoard, RawFenParseError};
 
+use super::super::token;
 use super::super::{
     msg::{Command, Register},
     str::{OptName, RegisterName},
@@ -15,6 +18,8 @@ pub enum Error {
     ExtraToken(String),
     #[error(transparent)]
     UnexpectedEol(#[from] EolError),
+    #[error(transparent)]
+    StrippedInvisible(#[from] StrippedInvisibleError),
     #[error("no \"name\" in \"setoption\"")]
     SetOptionNoName,
     #[error("cannot convert option name: {0}")]
@@ -39,19 +44,172 @@ pub enum Error {
     InvalidGo(#[from] go::Error),
 }
 
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnexpectedToken(_)
+            | Self::ExtraToken(_)
+            | Self::SetOptionNoName
+            | Self::RegisterNoCode
+            | Self::PositionNoMoves
+            | Self::NoPosition
+            | Self::StrippedInvisible(_) => ErrorKind::Syntax,
+            Self::UnexpectedEol(_) => ErrorKind::Eol,
+            Self::SetOptionBadName(_)
+            | Self::RegisterBadName(_)
+            | Self::InvalidFen(_)
+            | Self::InvalidMove { .. } => ErrorKind::Semantic,
+            Self::InvalidGo(err) => err.kind(),
+        }
+    }
+}
+
+/// Warning along with the byte range in the source line it refers to, if known
+pub type SpannedError = (Error, Option<Range<usize>>);
+
+const KEYWORDS: &[&str] = &[
+    "uci",
+    "debug",
+    "isready",
+    "setoption",
+    "register",
+    "ucinewgame",
+    "position",
+    "go",
+    "stop",
+    "ponderhit",
+    "quit",
+];
+
+const NO_ARGS_KEYWORDS: &[&str] = &["uci", "isready", "ucinewgame", "stop", "ponderhit", "quit"];
+
+impl Command {
+    /// Parses `line`, which may contain several commands collapsed into one line
+    ///
+    /// Commands without arguments (like `isready` or `stop`) end at the next command keyword, so
+    /// `isready uci` yields two commands. Other commands take the rest of the line, as they may
+    /// legitimately contain keywords in their arguments (e.g. in `setoption` value).
+    pub fn split_commands(line: &str, warn: &mut impl Warn<Error>) -> Vec<Command> {
+        let is_keyword = |tok: &&Token| KEYWORDS.contains(&tok.as_str());
+        let tokens: Vec<_> = token::tokenize(line).collect();
+        let mut result = Vec::new();
+        let mut start = 0;
+        while start < tokens.len() {
+            let end = match tokens[start..].iter().position(is_keyword) {
+                Some(pos) if NO_ARGS_KEYWORDS.contains(&tokens[start + pos].as_str()) => {
+                    let kw = start + pos + 1;
+                    tokens[kw..]
+                        .iter()
+                        .position(is_keyword)
+                        .map_or(tokens.len(), |pos| kw + pos)
+                }
+                _ => tokens.len(),
+            };
+            result.extend(parse(&mut &tokens[start..end], warn));
+            start = end;
+        }
+        result
+    }
+
+    /// Parses `line`, returning each warning along with the byte range in `line` it refers to
+    ///
+    /// The range is known for warnings about a specific token (i.e. [`Error::UnexpectedToken`]
+    /// and [`Error::ExtraToken`]), and for [`Error::UnexpectedEol`], which points to the end of
+    /// the line. For other warnings, `None` is returned instead of the range.
+    pub fn parse_line_spanned(line: &str) -> (Option<Command>, Vec<SpannedError>) {
+        let tokens: Vec<_> = token::tokenize(line).collect();
+        let mut warn = Spans {
+            line,
+            warnings: Vec::new(),
+        };
+        let cmd = parse_impl(&mut &tokens[..], false, &mut warn);
+        (cmd, warn.warnings)
+    }
+
+    /// Parses `line` like [`super::Parse::parse_line`], but also accepts non-standard `k`/`M`/`G`
+    /// suffixes for `nodes` in `go` (see [`super::parse_go_with_suffixes`])
+    pub fn parse_line_with_suffixes(line: &str, warn: &mut impl Warn<Error>) -> Option<Command> {
+        let tokens: Vec<_> = token::tokenize(line).collect();
+        parse_impl(&mut &tokens[..], true, &mut NoSpans(warn))
+    }
+}
+
+/// Sink which is also told the token the warning refers to, if any
+trait TokenWarn: Warn<Error> {
+    #[inline]
+    fn warn_at(&mut self, error: Error, _tok: &Token) {
+        self.warn(error);
+    }
+}
+
+struct NoSpans<'a, W>(&'a mut W);
+
+impl<W: Warn<Error>> Warn<Error> for NoSpans<'_, W> {
+    #[inline]
+    fn warn(&mut self, error: Error) {
+        self.0.warn(error);
+    }
+}
+
+impl<W: Warn<Error>> TokenWarn for NoSpans<'_, W> {}
+
+struct Spans<'a> {
+    line: &'a str,
+    warnings: Vec<SpannedError>,
+}
+
+impl Warn<Error> for Spans<'_> {
+    fn warn(&mut self, error: Error) {
+        let span = match error {
+            Error::UnexpectedEol(_) => Some(self.line.len()..self.line.len()),
+            _ => None,
+        };
+        self.warnings.push((error, span));
+    }
+}
+
+impl TokenWarn for Spans<'_> {
+    fn warn_at(&mut self, error: Error, tok: &Token) {
+        // All the tokens are slices of `line`, so the offset is found from the token address.
+        let start = tok.as_ptr() as usize - self.line.as_ptr() as usize;
+        self.warnings.push((error, Some(start..start + tok.len())));
+    }
+}
+
+fn warn_unexpected_at(tok: &Token, warn: &mut impl TokenWarn) {
+    warn.warn_at(Error::UnexpectedToken(tok.to_string()), tok);
+}
+
+fn warn_extra_at(tokens: &[&Token], warn: &mut impl TokenWarn) {
+    for tok in tokens {
+        warn.warn_at(Error::ExtraToken(tok.to_string()), tok);
+    }
+}
+
+#[inline]
 pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Command> {
+    parse_impl(tokens, false, &mut NoSpans(warn))
+}
+
+fn parse_impl(
+    tokens: &mut &[&Token],
+    go_suffixes: bool,
+    warn: &mut impl TokenWarn,
+) -> Option<Command> {
     let result = (|| loop {
-        match tok::next(tokens)?.as_str() {
+        let kw = tok::next(tokens)?;
+        match kw.as_str() {
             "uci" => return Some(Command::Uci),
             "debug" => {
-                return match tok::next_warn(tokens, warn)?.as_str() {
+                let arg = tok::next_warn(tokens, warn)?;
+                return match arg.as_str() {
                     "on" => Some(Command::Debug(true)),
                     "off" => Some(Command::Debug(false)),
-                    tok => {
-                        warn.warn(Error::UnexpectedToken(tok.to_string()));
+                    _ => {
+                        warn_unexpected_at(arg, warn);
                         None
                     }
-                }
+                };
             }
             "isready" => return Some(Command::IsReady),
             "setoption" => {
@@ -63,7 +221,8 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Comm
                 return Some(Command::SetOption { name, value });
             }
             "register" => {
-                return match tok::next_warn(tokens, warn)?.as_str() {
+                let arg = tok::next_warn(tokens, warn)?;
+                return match arg.as_str() {
                     "later" => Some(Command::Register(Register::Later)),
                     "name" => {
                         let (name, code) = tok::split(tokens, "code", Error::RegisterNoCode, warn);
@@ -73,27 +232,26 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Comm
                         let code = UciString::from_tokens(code);
                         Some(Command::Register(Register::Now { name, code }))
                     }
-                    tok => {
-                        warn.warn(Error::UnexpectedToken(tok.to_string()));
+                    _ => {
+                        warn_unexpected_at(arg, warn);
                         None
                     }
-                }
+                };
             }
             "ucinewgame" => return Some(Command::UciNewGame),
             "position" => {
                 let (mut position, moves) =
                     tok::split(tokens, "moves", Error::PositionNoMoves, warn);
                 *tokens = &[];
-                let startpos = match tok::next(&mut position).map(Token::as_str) {
+                let kind = tok::next(&mut position);
+                let startpos = match kind.map(Token::as_str) {
                     Some("startpos") => {
-                        if !position.is_empty() {
-                            warn.warn(Error::ExtraToken(position[0].to_string()));
-                        }
+                        warn_extra_at(position, warn);
                         RawBoard::initial()
                     }
                     Some("fen") => RawBoard::from_fen(&position.join(" ")).or_warn(warn)?,
-                    Some(tok) => {
-                        warn.warn(Error::UnexpectedToken(tok.to_string()));
+                    Some(_) => {
+                        warn_unexpected_at(kind.unwrap(), warn);
                         return None;
                     }
                     None => {
@@ -112,16 +270,21 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Comm
                     .or_warn(warn)?;
                 return Some(Command::Position { startpos, moves });
             }
-            "go" => return Some(Command::Go(go::parse(tokens, &mut warn.adapt()))),
+            "go" => {
+                let go = if go_suffixes {
+                    go::parse_with_suffixes(tokens, &mut warn.adapt())
+                } else {
+                    go::parse(tokens, &mut warn.adapt())
+                };
+                return Some(Command::Go(go));
+            }
             "stop" => return Some(Command::Stop),
             "ponderhit" => return Some(Command::PonderHit),
             "quit" => return Some(Command::Quit),
-            tok => warn.warn(Error::UnexpectedToken(tok.to_string())),
+            _ => warn_unexpected_at(kw, warn),
         }
     })();
-    if !tokens.is_empty() {
-        warn.warn(Error::ExtraToken(tokens[0].to_string()));
-    }
+    warn_extra_at(tokens, warn);
     result
 }
 
@@ -172,3 +335,264 @@ pub fn fmt(src: &Command, f: &mut impl PushTokens) {
         Command::Quit => f.push_kw("quit"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{super::msg::Go, Fmt, Parse};
+    use super::*;
+    use std::str::FromStr;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_extra_tokens() {
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line("ucinewgame foo bar baz", &mut warn);
+        assert_eq!(cmd, Some(Command::UciNewGame));
+        assert_eq!(
+            warn.0,
+            vec![
+                Error::ExtraToken("foo".to_string()),
+                Error::ExtraToken("bar".to_string()),
+                Error::ExtraToken("baz".to_string()),
+            ]
+        );
+
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line("position startpos foo bar moves e2e4", &mut warn);
+        assert_eq!(
+            cmd,
+            Some(Command::Position {
+                startpos: RawBoard::initial(),
+                moves: vec!["e2e4".parse().unwrap()],
+            })
+        );
+        assert_eq!(warn.0.len(), 2);
+    }
+
+    #[test]
+    fn test_no_args_extra_token() {
+        for (line, expected) in [
+            ("uci foo", Command::Uci),
+            ("isready foo", Command::IsReady),
+            ("ucinewgame foo", Command::UciNewGame),
+            ("stop foo", Command::Stop),
+            ("ponderhit foo", Command::PonderHit),
+            ("quit foo", Command::Quit),
+        ] {
+            let mut warn = CollectAll::default();
+            assert_eq!(Command::parse_line(line, &mut warn), Some(expected));
+            assert_eq!(warn.0, vec![Error::ExtraToken("foo".to_string())]);
+        }
+    }
+
+    #[test]
+    fn test_go_suffixes() {
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line_with_suffixes("go nodes 1M", &mut warn);
+        assert_eq!(
+            cmd,
+            Some(Command::Go(Go {
+                nodes: Some(1_000_000),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(
+            warn.0,
+            vec![Error::InvalidGo(go::Error::NonStandardSuffix(
+                "1M".to_string()
+            ))]
+        );
+
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line("go nodes 1M", &mut warn);
+        assert_eq!(cmd, Some(Command::Go(Go::default())));
+        assert_eq!(warn.0.len(), 1);
+    }
+
+    #[test]
+    fn test_spanned() {
+        let line = "  frob\tisready  foo   isready ";
+        let (cmd, warnings) = Command::parse_line_spanned(line);
+        assert_eq!(cmd, Some(Command::IsReady));
+        assert_eq!(
+            warnings,
+            vec![
+                (Error::UnexpectedToken("frob".to_string()), Some(2..6)),
+                (Error::ExtraToken("foo".to_string()), Some(16..19)),
+                (Error::ExtraToken("isready".to_string()), Some(22..29)),
+            ]
+        );
+        for (_, span) in &warnings {
+            let span = span.clone().unwrap();
+            assert!(!line[span].contains(char::is_whitespace));
+        }
+
+        let (_, warnings) = Command::parse_line_spanned("isready isready");
+        assert_eq!(
+            warnings,
+            vec![(Error::ExtraToken("isready".to_string()), Some(8..15))]
+        );
+
+        let (cmd, warnings) = Command::parse_line_spanned("debug on on");
+        assert_eq!(cmd, Some(Command::Debug(true)));
+        assert_eq!(
+            warnings,
+            vec![(Error::ExtraToken("on".to_string()), Some(9..11))]
+        );
+
+        let (_, warnings) = Command::parse_line_spanned("position startpos startpos moves");
+        assert_eq!(
+            warnings,
+            vec![(Error::ExtraToken("startpos".to_string()), Some(18..26))]
+        );
+
+        let (cmd, warnings) = Command::parse_line_spanned("debug ");
+        assert_eq!(cmd, None);
+        assert_eq!(warnings, vec![(Error::UnexpectedEol(EolError), Some(6..6))]);
+    }
+
+    #[test]
+    fn test_strip_invisible() {
+        let mut warn = CollectAll::default();
+        assert_eq!(Command::parse_line("\u{feff}uci", &mut warn), None);
+        assert_eq!(
+            warn.0,
+            vec![Error::UnexpectedToken("\u{feff}uci".to_string())]
+        );
+
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line_strip_invisible("\u{feff}uci", &mut warn);
+        assert_eq!(cmd, Some(Command::Uci));
+        assert_eq!(
+            warn.0,
+            vec![Error::StrippedInvisible(StrippedInvisibleError)]
+        );
+
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line_strip_invisible("isready\u{200b}", &mut warn);
+        assert_eq!(cmd, Some(Command::IsReady));
+        assert_eq!(warn.0.len(), 1);
+
+        let mut warn = CollectAll::default();
+        let cmd =
+            Command::parse_line_strip_invisible("\u{200b} debug \u{2060}on\u{200d}", &mut warn);
+        assert_eq!(cmd, Some(Command::Debug(true)));
+        assert_eq!(warn.0.len(), 1);
+
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line_strip_invisible("ucinewgame", &mut warn);
+        assert_eq!(cmd, Some(Command::UciNewGame));
+        assert!(warn.0.is_empty());
+    }
+
+    #[test]
+    fn test_setoption_layout() {
+        let cmd = Command::SetOption {
+            name: OptName::from_str("X").unwrap(),
+            value: Some(UciString::from("3")),
+        };
+        assert_eq!(cmd.fmt_line(), "setoption name X value 3");
+
+        let cmd = Command::SetOption {
+            name: OptName::from_str("Clear Hash").unwrap(),
+            value: None,
+        };
+        assert_eq!(cmd.fmt_line(), "setoption name Clear Hash");
+    }
+
+    #[test]
+    fn test_split_commands() {
+        let mut warn = CollectAll::default();
+        assert_eq!(
+            Command::split_commands("isready uci", &mut warn),
+            vec![Command::IsReady, Command::Uci]
+        );
+        assert!(warn.0.is_empty());
+
+        let cmds = Command::split_commands("foo stop bar isready go depth 3 quit", &mut warn);
+        assert_eq!(
+            cmds,
+            vec![
+                Command::Stop,
+                Command::IsReady,
+                Command::Go(Go {
+                    depth: Some(3),
+                    ..Default::default()
+                }),
+            ]
+        );
+        assert_eq!(
+            warn.0,
+            vec![
+                Error::UnexpectedToken("foo".to_string()),
+                Error::ExtraToken("bar".to_string()),
+                Error::InvalidGo(go::Error::UnexpectedToken("quit".to_string())),
+            ]
+        );
+
+        let mut warn = CollectAll::default();
+        let cmds = Command::split_commands("setoption name Log value stop uci", &mut warn);
+        assert_eq!(cmds.len(), 1);
+        assert!(warn.0.is_empty());
+        assert!(Command::split_commands("", &mut warn).is_empty());
+    }
+
+    #[test]
+    fn test_embedded_keywords() {
+        for cmd in [
+            Command::SetOption {
+                name: OptName::from_str("Book File").unwrap(),
+                value: Some(UciString::from("value type name value")),
+            },
+            Command::Register(Register::Now {
+                name: RegisterName::from_str("Stefan MK").unwrap(),
+                code: UciString::from("code name later code"),
+            }),
+        ] {
+            let mut warn = CollectAll::default();
+            assert_eq!(Command::parse_line(&cmd.fmt_line(), &mut warn), Some(cmd));
+            assert!(warn.0.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_initial_fen_as_startpos() {
+        let mut warn = CollectAll::default();
+        let cmd = Command::parse_line(
+            "position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 moves e2e4",
+            &mut warn,
+        )
+        .unwrap();
+        assert_eq!(cmd.fmt_line(), "position startpos moves e2e4");
+
+        let cmd = Command::parse_line(
+            "position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3 7 moves",
+            &mut warn,
+        )
+        .unwrap();
+        assert_eq!(
+            cmd.fmt_line(),
+            "position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3 7 moves"
+        );
+        assert!(warn.0.is_empty());
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let kind = |line| {
+            let mut warn = CollectAll::<Error>::default();
+            Command::parse_line(line, &mut warn);
+            warn.0.iter().map(Error::kind).collect::<Vec<_>>()
+        };
+        assert_eq!(kind("isready now"), vec![ErrorKind::Syntax]);
+        assert_eq!(kind("debug"), vec![ErrorKind::Eol]);
+        assert_eq!(kind("setoption name type"), vec![ErrorKind::Semantic]);
+        assert_eq!(
+            kind("position startpos moves e2e9"),
+            vec![ErrorKind::Semantic]
+        );
+        assert_eq!(kind("go depth"), vec![ErrorKind::Eol]);
+        assert_eq!(kind("go depth x"), vec![ErrorKind::Syntax]);
+        assert_eq!(kind("go mate -3"), vec![ErrorKind::Semantic]);
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use super::super::msg::{Command, Direction, Message, UciEvent};
+use super::{command, message, prelude::*, Fmt, Parse};
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("cannot parse command: {0}")]
+    BadCommand(#[source] command::Error),
+    #[error("cannot parse message: {0}")]
+    BadMessage(#[source] message::Error),
+}
+
+pub fn parse(dir: Direction, line: &str, warn: &mut impl Warn<Error>) -> Option<UciEvent> {
+    match dir {
+        Direction::ToEngine => Command::parse_line(line, &mut warn.adapt_map(Error::BadCommand))
+            .map(UciEvent::ToEngine),
+        Direction::FromEngine => Message::parse_line(line, &mut warn.adapt_map(Error::BadMessage))
+            .map(UciEvent::FromEngine),
+    }
+}
+
+impl fmt::Display for UciEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciEvent::ToEngine(cmd) => write!(f, "> {}", cmd.fmt_line()),
+            UciEvent::FromEngine(msg) => write!(f, "< {}", msg.fmt_line()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::transcript;
+    use super::*;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_event() {
+        let mut warn = CollectAll::default();
+        let cmd = parse(Direction::ToEngine, "go  depth 5", &mut warn).unwrap();
+        let msg = parse(Direction::FromEngine, "bestmove e2e4", &mut warn).unwrap();
+        assert!(warn.0.is_empty());
+        assert_eq!(cmd.direction(), Direction::ToEngine);
+        assert_eq!(msg.direction(), Direction::FromEngine);
+        assert_eq!(cmd.to_string(), "> go depth 5");
+        assert_eq!(msg.to_string(), "< bestmove e2e4");
+
+        let mut transcript_warn = CollectAll::default();
+        let lines = transcript::parse(&format!("{}\n{}\n", cmd, msg), &mut transcript_warn);
+        assert!(transcript_warn.0.is_empty());
+        assert_eq!(lines, vec![cmd, msg]);
+
+        assert_eq!(parse(Direction::FromEngine, "isready", &mut warn), None);
+        assert_eq!(
+            warn.0,
+            vec![Error::BadMessage(message::Error::UnexpectedToken(
+                "isready".to_string()
+            ))]
+        );
+    }
+}
@@ -18,9 +18,65 @@ pub enum Error {
         #[source]
         error: ParseIntError,
     },
+    #[error("negative mate distance {0}, ignoring")]
+    NegativeMate(i64),
+    #[error("non-standard suffix in \"{0}\"")]
+    NonStandardSuffix(String),
+    #[error("value \"{0}\" is too large")]
+    TooLarge(String),
+    #[error("empty searchmoves, no moves will be searched")]
+    EmptySearchMoves,
 }
 
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnexpectedToken(_) | Self::InvalidIntSub { .. } | Self::NonStandardSuffix(_) => {
+                ErrorKind::Syntax
+            }
+            Self::UnexpectedEol(_) => ErrorKind::Eol,
+            Self::Duplicate(_)
+            | Self::InvalidSearchMove(_)
+            | Self::NegativeMate(_)
+            | Self::TooLarge(_)
+            | Self::EmptySearchMoves => ErrorKind::Semantic,
+        }
+    }
+}
+
+fn parse_suffixed(tok: &Token, warn: &mut impl Warn<Error>) -> Option<Option<u32>> {
+    let (num, mul) = match tok.as_bytes().last()?.to_ascii_lowercase() {
+        b'k' => (&tok[..tok.len() - 1], 1_000),
+        b'm' => (&tok[..tok.len() - 1], 1_000_000),
+        b'g' => (&tok[..tok.len() - 1], 1_000_000_000),
+        _ => return None,
+    };
+    let num: u64 = num.parse().ok()?;
+    warn.warn(Error::NonStandardSuffix(tok.to_string()));
+    let value = num
+        .checked_mul(mul)
+        .and_then(|v| u32::try_from(v).ok())
+        .or_warn_with(Error::TooLarge(tok.to_string()), warn);
+    Some(value)
+}
+
+/// Parses the arguments of `go`
+///
+/// If an item is repeated, [`Error::Duplicate`] is reported and the last successfully parsed
+/// occurrence wins.
+#[inline]
 pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Go {
+    parse_impl(tokens, false, warn)
+}
+
+/// Parses the arguments of `go` like [`parse`], but also accepts `nodes` with non-standard
+/// suffixes `k`, `M` and `G` (case-insensitive), reporting [`Error::NonStandardSuffix`]
+#[inline]
+pub fn parse_with_suffixes(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Go {
+    parse_impl(tokens, true, warn)
+}
+
+fn parse_impl(tokens: &mut &[&Token], suffixes: bool, warn: &mut impl Warn<Error>) -> Go {
     let mut searchmoves = None;
     let mut ponder = None;
     let mut infinite = None;
@@ -35,19 +91,32 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Go {
     let mut movetime = None;
 
     while let Some(item) = tok::next(tokens).map(Token::as_str) {
+        // The optional second argument is a pre-parsed value for the next token. If it's
+        // `Some`, the token is consumed and the value is used instead of parsing an integer
+        // (`Some(None)` means that the token is consumed, but the value is not updated).
         macro_rules! parse_int {
-            ($ident:ident) => {{
+            ($ident:ident) => {
+                parse_int!($ident, None)
+            };
+            ($ident:ident, $pre:expr) => {{
                 if $ident.is_some() {
                     warn.warn(Error::Duplicate(stringify!($ident)));
                 }
-                if let Some(value) = tok::parse_map(
-                    tokens,
-                    |error| Error::InvalidIntSub {
-                        name: stringify!($ident),
-                        error,
-                    },
-                    warn,
-                ) {
+                let value = match $pre {
+                    Some(value) => {
+                        *tokens = &tokens[1..];
+                        value
+                    }
+                    None => tok::parse_map(
+                        tokens,
+                        |error| Error::InvalidIntSub {
+                            name: stringify!($ident),
+                            error,
+                        },
+                        warn,
+                    ),
+                };
+                if let Some(value) = value {
                     $ident = Some(value);
                 }
             }};
@@ -55,14 +124,15 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Go {
 
         match item {
             "searchmoves" => {
-                if ponder.is_some() {
+                if searchmoves.is_some() {
                     warn.warn(Error::Duplicate("searchmoves"));
                 }
-                searchmoves = Some(movevec::parse(
-                    tokens,
-                    false,
-                    &mut warn.adapt_map(Error::InvalidSearchMove),
-                ));
+                let moves =
+                    movevec::parse(tokens, false, &mut warn.adapt_map(Error::InvalidSearchMove));
+                if moves.is_empty() {
+                    warn.warn(Error::EmptySearchMoves);
+                }
+                searchmoves = Some(moves);
             }
             "ponder" => {
                 if ponder.is_some() {
@@ -81,9 +151,25 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Go {
             "winc" => parse_int!(winc),
             "binc" => parse_int!(binc),
             "movestogo" => parse_int!(movestogo),
-            "mate" => parse_int!(mate),
+            "mate" => parse_int!(
+                mate,
+                tokens
+                    .first()
+                    .and_then(|t| t.parse::<i64>().ok())
+                    .filter(|&val| val < 0)
+                    .map(|val| {
+                        warn.warn(Error::NegativeMate(val));
+                        None
+                    })
+            ),
             "depth" => parse_int!(depth),
-            "nodes" => parse_int!(nodes),
+            "nodes" => parse_int!(
+                nodes,
+                tokens
+                    .first()
+                    .filter(|_| suffixes)
+                    .and_then(|tok| parse_suffixed(tok, warn))
+            ),
             "movetime" => parse_int!(movetime),
             tok => warn.warn(Error::UnexpectedToken(tok.to_string())),
         }
@@ -144,3 +230,157 @@ pub fn fmt(src: &Go, f: &mut impl PushTokens) {
         f.push_tag("movetime", &val.as_millis());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::token;
+    use super::*;
+    use std::num::NonZeroU64;
+    use wurm::CollectAll;
+
+    fn parse_str(s: &str) -> (Go, Vec<Error>) {
+        let tokens: Vec<_> = token::tokenize(s).collect();
+        let mut warn = CollectAll::default();
+        let go = parse(&mut &tokens[..], &mut warn);
+        (go, warn.0)
+    }
+
+    fn parse_str_with_suffixes(s: &str) -> (Go, Vec<Error>) {
+        let tokens: Vec<_> = token::tokenize(s).collect();
+        let mut warn = CollectAll::default();
+        let go = parse_with_suffixes(&mut &tokens[..], &mut warn);
+        (go, warn.0)
+    }
+
+    #[test]
+    fn test_duplicate_last_wins() {
+        let (go, warns) = parse_str("wtime 1000 wtime 2000");
+        assert_eq!(go.wtime, Some(Duration::from_millis(2000)));
+        assert_eq!(warns, vec![Error::Duplicate("wtime")]);
+
+        let (go, warns) = parse_str("searchmoves e2e4 depth 3 searchmoves d2d4");
+        assert_eq!(go.searchmoves, Some(vec!["d2d4".parse().unwrap()]));
+        assert_eq!(go.depth, Some(3));
+        assert_eq!(warns, vec![Error::Duplicate("searchmoves")]);
+
+        // `ponder` before `searchmoves` is not a duplicate
+        let (go, warns) = parse_str("ponder searchmoves e2e4");
+        assert_eq!(go.ponder, Some(()));
+        assert_eq!(go.searchmoves, Some(vec!["e2e4".parse().unwrap()]));
+        assert!(warns.is_empty());
+    }
+
+    #[test]
+    fn test_infinite_order_independent() {
+        let (go1, warns1) = parse_str("infinite depth 10");
+        let (go2, warns2) = parse_str("depth 10 infinite");
+        assert_eq!(go1, go2);
+        assert_eq!(go1.infinite, Some(()));
+        assert_eq!(go1.depth, Some(10));
+        assert!(warns1.is_empty());
+        assert_eq!(warns1, warns2);
+    }
+
+    #[test]
+    fn test_empty_searchmoves() {
+        let (go, warns) = parse_str("searchmoves depth 10");
+        assert_eq!(go.searchmoves, Some(Vec::new()));
+        assert_eq!(go.depth, Some(10));
+        assert_eq!(warns, vec![Error::EmptySearchMoves]);
+
+        let (go, warns) = parse_str("searchmoves");
+        assert_eq!(go.searchmoves, Some(Vec::new()));
+        assert_eq!(warns, vec![Error::EmptySearchMoves]);
+    }
+
+    #[test]
+    fn test_negative_mate() {
+        let (go, warns) = parse_str("mate -5 depth 3");
+        assert_eq!(go.mate, None);
+        assert_eq!(go.depth, Some(3));
+        assert_eq!(warns, vec![Error::NegativeMate(-5)]);
+
+        let (go, warns) = parse_str("mate 3 mate -5");
+        assert_eq!(go.mate, Some(3));
+        assert_eq!(
+            warns,
+            vec![Error::Duplicate("mate"), Error::NegativeMate(-5)]
+        );
+    }
+
+    #[test]
+    fn test_nodes_suffix() {
+        let (go, warns) = parse_str_with_suffixes("nodes 1M depth 5");
+        assert_eq!(go.nodes, Some(1_000_000));
+        assert_eq!(go.depth, Some(5));
+        assert_eq!(warns, vec![Error::NonStandardSuffix("1M".to_string())]);
+
+        let (go, warns) = parse_str_with_suffixes("nodes 15k");
+        assert_eq!(go.nodes, Some(15_000));
+        assert_eq!(warns.len(), 1);
+
+        let (go, warns) = parse_str_with_suffixes("nodes 1000");
+        assert_eq!(go.nodes, Some(1000));
+        assert!(warns.is_empty());
+
+        let (go, warns) = parse_str_with_suffixes("nodes 5G");
+        assert_eq!(go.nodes, None);
+        assert_eq!(
+            warns,
+            vec![
+                Error::NonStandardSuffix("5G".to_string()),
+                Error::TooLarge("5G".to_string()),
+            ]
+        );
+
+        let (go, warns) = parse_str_with_suffixes("nodes 100 nodes 2k");
+        assert_eq!(go.nodes, Some(2_000));
+        assert_eq!(
+            warns,
+            vec![
+                Error::Duplicate("nodes"),
+                Error::NonStandardSuffix("2k".to_string()),
+            ]
+        );
+
+        let (go, warns) = parse_str("nodes 1M");
+        assert_eq!(go.nodes, None);
+        assert!(matches!(
+            warns.as_slice(),
+            [Error::InvalidIntSub { name: "nodes", .. }]
+        ));
+    }
+
+    #[test]
+    fn test_searchmoves_infinite_fmt() {
+        let go = Go {
+            searchmoves: Some(vec!["e2e4".parse().unwrap()]),
+            infinite: Some(()),
+            ..Default::default()
+        };
+        let mut line = UciString::new();
+        fmt(&go, &mut line);
+        assert_eq!(line.as_str(), "searchmoves e2e4 infinite");
+        assert_eq!(parse_str(line.as_str()), (go, vec![]));
+    }
+
+    #[test]
+    fn test_movestogo_roundtrip() {
+        let (go, warns) = parse_str("wtime 1 btime 1 movestogo 40");
+        assert!(warns.is_empty());
+        assert_eq!(go.movestogo, NonZeroU64::new(40));
+        let mut line = UciString::new();
+        fmt(&go, &mut line);
+        assert_eq!(line.as_str(), "wtime 1 btime 1 movestogo 40");
+
+        let (go, warns) = parse_str("movestogo 0");
+        assert_eq!(go.movestogo, None);
+        assert!(matches!(
+            warns.as_slice(),
+            [Error::InvalidIntSub {
+                name: "movestogo",
+                ..
+            }]
+        ));
+    }
+}
@@ -18,10 +18,31 @@ pub enum Error {
     BadMoveVec(#[from] movevec::Error),
     #[error("cannot parse score: {0}")]
     BadScore(#[from] score::Error),
+    #[error("second score \"{0}\" after score, discarding")]
+    ScoreAfterScore(String),
+}
+
+fn skip_extra_scores(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) {
+    while let Some(&kind) = tokens
+        .first()
+        .filter(|t| matches!(t.as_str(), "cp" | "mate"))
+    {
+        *tokens = &tokens[1..];
+        if tokens.first().is_some_and(|t| t.parse::<i64>().is_ok()) {
+            *tokens = &tokens[1..];
+        }
+        while tokens
+            .first()
+            .is_some_and(|t| matches!(t.as_str(), "lowerbound" | "upperbound"))
+        {
+            *tokens = &tokens[1..];
+        }
+        warn.warn(Error::ScoreAfterScore(kind.to_string()));
+    }
 }
 
 fn make_permille(val: u64, warn: &mut impl Warn<Error>) -> Permille {
-    if val >= 1000 {
+    if val > 1000 {
         warn.warn(Error::PermilleTruncated { src_value: val });
     }
     Permille::new_truncated(val)
@@ -35,7 +56,11 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Info
         "nodes" => Some(Info::Nodes(tok::parse(tokens, warn)?)),
         "pv" => Some(Info::Pv(movevec::parse(tokens, true, &mut warn.adapt()))),
         "multipv" => Some(Info::MultiPv(tok::parse(tokens, warn)?)),
-        "score" => Some(Info::Score(score::parse(tokens, &mut warn.adapt())?)),
+        "score" => {
+            let score = score::parse(tokens, &mut warn.adapt())?;
+            skip_extra_scores(tokens, warn);
+            Some(Info::Score(score))
+        }
         "currmove" => Some(Info::CurrMove(tok::parse(tokens, warn)?)),
         "currmovenumber" => Some(Info::CurrMoveNumber(tok::parse(tokens, warn)?)),
         "hashfull" => Some(Info::HashFull(make_permille(
@@ -1,5 +1,5 @@
 use super::super::{
-    msg::{Id, Message},
+    msg::{Id, Info, Message},
     str::OptName,
 };
 use super::{info, optbody, prelude::*, tok, tristatus};
@@ -13,6 +13,8 @@ pub enum Error {
     ExtraToken(String),
     #[error(transparent)]
     UnexpectedEol(#[from] EolError),
+    #[error(transparent)]
+    StrippedInvisible(#[from] StrippedInvisibleError),
     #[error("invalid best move, assuming null move")]
     InvalidBestmove(#[source] uci::RawParseError),
     #[error("invalid ponder move")]
@@ -35,9 +37,45 @@ pub enum Error {
     OptionBadName(#[source] StrError),
     #[error("invalid option body: {0}")]
     OptionBadBody(#[from] optbody::Error),
+    #[error("selective depth {seldepth} is less than depth {depth}")]
+    SelDepthLessThanDepth { depth: u32, seldepth: u32 },
+    #[error("trailing tokens after \"bestmove\": {0}")]
+    TrailingTokens(String),
+}
+
+fn check_seldepth(info: &[Info], warn: &mut impl Warn<Error>) {
+    let depth = info.iter().find_map(|inf| match inf {
+        Info::Depth(val) => Some(*val),
+        _ => None,
+    });
+    let seldepth = info.iter().find_map(|inf| match inf {
+        Info::SelDepth(val) => Some(*val),
+        _ => None,
+    });
+    if let (Some(depth), Some(seldepth)) = (depth, seldepth) {
+        if seldepth < depth {
+            warn.warn(Error::SelDepthLessThanDepth { depth, seldepth });
+        }
+    }
 }
 
 pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Message> {
+    parse_impl(tokens, false, warn)
+}
+
+/// Parses the message like [`parse`], but also accepts non-standard tokens after `bestmove`
+/// (like `bestmove e2e4 ponder e7e5 depth 20`), reporting them as a single
+/// [`Error::TrailingTokens`]
+#[inline]
+pub fn parse_trailing_ok(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Message> {
+    parse_impl(tokens, true, warn)
+}
+
+fn parse_impl(
+    tokens: &mut &[&Token],
+    trailing_ok: bool,
+    warn: &mut impl Warn<Error>,
+) -> Option<Message> {
     let result = (|| loop {
         match tok::next(tokens)?.as_str() {
             "id" => {
@@ -67,13 +105,21 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Mess
                     .or_warn_map(Error::InvalidBestmove, warn)
                     .unwrap_or(UciMove::Null);
                 let ponder = (|| {
-                    let tok = tok::next(tokens)?;
-                    if tok != "ponder" {
-                        warn.warn(Error::UnexpectedToken(tok.to_string()));
+                    let tok = tokens.first()?;
+                    if *tok != "ponder" {
+                        if !trailing_ok {
+                            warn.warn(Error::UnexpectedToken(tok.to_string()));
+                            *tokens = &tokens[1..];
+                        }
                         return None;
                     }
+                    *tokens = &tokens[1..];
                     tok::parse_map(tokens, Error::InvalidPonder, warn)
                 })();
+                if trailing_ok && !tokens.is_empty() {
+                    warn.warn(Error::TrailingTokens(tokens.join(" ")));
+                    *tokens = &[];
+                }
                 return Some(Message::BestMove { bestmove, ponder });
             }
             "copyprotection" => {
@@ -103,6 +149,7 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Mess
                         info.push(inf);
                     }
                 }
+                check_seldepth(&info, warn);
                 return Some(Message::Info { info, string });
             }
             "option" => {
@@ -116,9 +163,7 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Mess
             tok => warn.warn(Error::UnexpectedToken(tok.to_string())),
         }
     })();
-    if !tokens.is_empty() {
-        warn.warn(Error::UnexpectedToken(tokens[0].to_string()));
-    }
+    tok::warn_extra(tokens, Error::ExtraToken, warn);
     result
 }
 
@@ -164,3 +209,362 @@ pub fn fmt(src: &Message, f: &mut impl PushTokens) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{
+        msg::OptBody,
+        str::OptComboVar,
+        token,
+        types::{Permille, TriStatus},
+    };
+    use super::super::{Fmt, Parse};
+    use super::*;
+    use crate::score::{Bound, BoundedRelScore, RelScore};
+    use std::{str::FromStr, time::Duration};
+    use wurm::CollectAll;
+
+    fn parse_str(s: &str) -> (Option<Message>, Vec<Error>) {
+        let mut warn = CollectAll::default();
+        let msg = Message::parse_line(s, &mut warn);
+        (msg, warn.0)
+    }
+
+    #[test]
+    fn test_seldepth_less_than_depth() {
+        let (msg, warns) = parse_str("info depth 20 seldepth 10");
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: vec![Info::Depth(20), Info::SelDepth(10)],
+                string: None,
+            })
+        );
+        assert_eq!(
+            warns,
+            vec![Error::SelDepthLessThanDepth {
+                depth: 20,
+                seldepth: 10
+            }]
+        );
+
+        let (_, warns) = parse_str("info depth 20 seldepth 25");
+        assert!(warns.is_empty());
+    }
+
+    #[test]
+    fn test_extra_tokens() {
+        let (msg, warns) = parse_str("readyok foo bar");
+        assert_eq!(msg, Some(Message::ReadyOk));
+        assert_eq!(
+            warns,
+            vec![
+                Error::ExtraToken("foo".to_string()),
+                Error::ExtraToken("bar".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_permille_boundary() {
+        let (msg, warns) = parse_str("info hashfull 1000 cpuload 1000");
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: vec![
+                    Info::HashFull(Permille::new(1000)),
+                    Info::CpuLoad(Permille::new(1000)),
+                ],
+                string: None,
+            })
+        );
+        assert!(warns.is_empty());
+
+        let (msg, warns) = parse_str("info hashfull 1001");
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: vec![Info::HashFull(Permille::new(1000))],
+                string: None,
+            })
+        );
+        assert_eq!(
+            warns,
+            vec![Error::BadInfo {
+                pos: 0,
+                error: info::Error::PermilleTruncated { src_value: 1001 },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strip_invisible_payload() {
+        let mut warn = CollectAll::default();
+        let msg = Message::parse_line_strip_invisible("\u{feff}info string a\u{200b}b", &mut warn);
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: Vec::new(),
+                string: Some(UciString::from("a\u{200b}b")),
+            })
+        );
+        assert_eq!(
+            warn.0,
+            vec![Error::StrippedInvisible(StrippedInvisibleError)]
+        );
+    }
+
+    #[test]
+    fn test_bestmove_trailing() {
+        let parse_str_trailing_ok = |s: &str| {
+            let tokens: Vec<_> = token::tokenize(s).collect();
+            let mut warn = CollectAll::default();
+            let msg = parse_trailing_ok(&mut &tokens[..], &mut warn);
+            (msg, warn.0)
+        };
+        let e2e4: UciMove = "e2e4".parse().unwrap();
+        let e7e5: UciMove = "e7e5".parse().unwrap();
+
+        let (msg, warns) = parse_str_trailing_ok("bestmove e2e4 ponder e7e5 depth 20");
+        assert_eq!(
+            msg,
+            Some(Message::BestMove {
+                bestmove: e2e4,
+                ponder: Some(e7e5),
+            })
+        );
+        assert_eq!(warns, vec![Error::TrailingTokens("depth 20".to_string())]);
+
+        let (msg, warns) = parse_str_trailing_ok("bestmove e2e4 depth 20");
+        assert_eq!(
+            msg,
+            Some(Message::BestMove {
+                bestmove: e2e4,
+                ponder: None,
+            })
+        );
+        assert_eq!(warns, vec![Error::TrailingTokens("depth 20".to_string())]);
+
+        let (_, warns) = parse_str_trailing_ok("bestmove e2e4 ponder e7e5");
+        assert!(warns.is_empty());
+
+        let (_, warns) = parse_str("bestmove e2e4 ponder e7e5 depth 20");
+        assert_eq!(
+            warns,
+            vec![
+                Error::ExtraToken("depth".to_string()),
+                Error::ExtraToken("20".to_string()),
+            ]
+        );
+        let (_, warns) = parse_str("bestmove e2e4 depth 20");
+        assert_eq!(
+            warns,
+            vec![
+                Error::UnexpectedToken("depth".to_string()),
+                Error::ExtraToken("20".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_score_after_score() {
+        let (msg, warns) = parse_str("info score mate 3 cp 900 depth 5");
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: vec![
+                    Info::Score(BoundedRelScore {
+                        score: RelScore::Mate {
+                            moves: 3,
+                            win: true
+                        },
+                        bound: Bound::Exact,
+                    }),
+                    Info::Depth(5),
+                ],
+                string: None,
+            })
+        );
+        assert_eq!(
+            warns,
+            vec![Error::BadInfo {
+                pos: 0,
+                error: info::Error::ScoreAfterScore("cp".to_string()),
+            }]
+        );
+
+        let score = |cp| {
+            Info::Score(BoundedRelScore {
+                score: RelScore::Cp(cp),
+                bound: Bound::Exact,
+            })
+        };
+
+        let (msg, warns) = parse_str("info score cp 10 cp 20 lowerbound depth 5");
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: vec![score(10), Info::Depth(5)],
+                string: None,
+            })
+        );
+        assert_eq!(
+            warns,
+            vec![Error::BadInfo {
+                pos: 0,
+                error: info::Error::ScoreAfterScore("cp".to_string()),
+            }]
+        );
+
+        let (msg, warns) = parse_str("info score cp 10 mate 3 upperbound cp 20 nodes 100");
+        assert_eq!(
+            msg,
+            Some(Message::Info {
+                info: vec![score(10), Info::Nodes(100)],
+                string: None,
+            })
+        );
+        assert_eq!(
+            warns,
+            vec![
+                Error::BadInfo {
+                    pos: 0,
+                    error: info::Error::ScoreAfterScore("mate".to_string()),
+                },
+                Error::BadInfo {
+                    pos: 0,
+                    error: info::Error::ScoreAfterScore("cp".to_string()),
+                },
+            ]
+        );
+    }
+
+    /// Returns the index of the variant of `msg`
+    ///
+    /// The match has no wildcard arm, so adding a new variant to `Message` fails to compile until
+    /// it's added here, and then [`test_roundtrip_all_variants`] fails until a fixture for it is
+    /// added.
+    fn variant_index(msg: &Message) -> usize {
+        match msg {
+            Message::Id(_) => 0,
+            Message::UciOk => 1,
+            Message::ReadyOk => 2,
+            Message::BestMove { .. } => 3,
+            Message::CopyProtection(_) => 4,
+            Message::Registration(_) => 5,
+            Message::Info { .. } => 6,
+            Message::Option { .. } => 7,
+        }
+    }
+
+    const VARIANT_COUNT: usize = 8;
+
+    fn fixtures() -> Vec<Message> {
+        let mv = |s: &str| UciMove::from_str(s).unwrap();
+        let name = |s: &str| OptName::from_str(s).unwrap();
+        let var = |s: &str| OptComboVar::from_str(s).unwrap();
+        vec![
+            Message::Id(Id::Name(UciString::from("Stockfish 15"))),
+            Message::Id(Id::Author(UciString::from("the Stockfish developers"))),
+            Message::UciOk,
+            Message::ReadyOk,
+            Message::BestMove {
+                bestmove: mv("e2e4"),
+                ponder: None,
+            },
+            Message::BestMove {
+                bestmove: mv("e7e8q"),
+                ponder: Some(mv("d2d1")),
+            },
+            Message::CopyProtection(TriStatus::Checking),
+            Message::Registration(TriStatus::Error),
+            Message::Info {
+                info: vec![
+                    Info::Depth(20),
+                    Info::SelDepth(31),
+                    Info::MultiPv(1),
+                    Info::Score(BoundedRelScore {
+                        score: RelScore::Cp(-31),
+                        bound: Bound::Lower,
+                    }),
+                    Info::Nodes(1234567),
+                    Info::Nps(987654),
+                    Info::HashFull(Permille::new(512)),
+                    Info::TbHits(7),
+                    Info::SbHits(0),
+                    Info::CpuLoad(Permille::new(1000)),
+                    Info::Time(Duration::from_millis(1500)),
+                    Info::Pv(vec![mv("e2e4"), mv("e7e5"), mv("g1f3")]),
+                ],
+                string: None,
+            },
+            Message::Info {
+                info: vec![
+                    Info::CurrMove(mv("d2d4")),
+                    Info::CurrMoveNumber(2),
+                    Info::Refutation(vec![mv("d1h5"), mv("g6h5")]),
+                    Info::CurrLine {
+                        cpu_num: 1,
+                        moves: vec![mv("d1h5"), mv("h7h6")],
+                    },
+                    Info::Score(BoundedRelScore {
+                        score: RelScore::Mate {
+                            moves: 3,
+                            win: false,
+                        },
+                        bound: Bound::Exact,
+                    }),
+                ],
+                string: Some(UciString::from("debug output")),
+            },
+            Message::Option {
+                name: name("Hash"),
+                body: OptBody::Spin {
+                    default: 16,
+                    min: 1,
+                    max: 33554432,
+                },
+            },
+            Message::Option {
+                name: name("Ponder"),
+                body: OptBody::Check(false),
+            },
+            Message::Option {
+                name: name("Style"),
+                body: OptBody::Combo {
+                    default: var("Normal"),
+                    vars: vec![var("Solid"), var("Normal"), var("Risky")],
+                },
+            },
+            Message::Option {
+                name: name("Clear Hash"),
+                body: OptBody::Button,
+            },
+            Message::Option {
+                name: name("NalimovPath"),
+                body: OptBody::String(UciString::from("c:\\chess\\tb")),
+            },
+            Message::Option {
+                name: name("Debug Log File"),
+                body: OptBody::String(UciString::new()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_roundtrip_all_variants() {
+        let fixtures = fixtures();
+        let mut covered = [false; VARIANT_COUNT];
+        for msg in &fixtures {
+            covered[variant_index(msg)] = true;
+        }
+        assert!(covered.iter().all(|&c| c), "not all variants covered");
+
+        for msg in fixtures {
+            let line = msg.fmt_line();
+            let (parsed, warns) = parse_str(&line);
+            assert!(warns.is_empty(), "warnings for {:?}: {:?}", line, warns);
+            assert_eq!(parsed.as_ref(), Some(&msg), "line {:?}", line);
+        }
+    }
+}
@@ -1,11 +1,16 @@
 mod command;
+mod event;
 mod go;
 mod info;
 mod message;
 mod movevec;
 mod optbody;
 mod score;
+#[cfg(feature = "serde")]
+pub mod serde;
 mod tok;
+mod tracker;
+mod transcript;
 mod tristatus;
 
 mod prelude {
@@ -13,23 +18,25 @@ mod prelude {
         str::{Error as StrError, UciString},
         token::{PushTokens, Token},
     };
-    pub use super::{tok::PushTokensExt, EolError};
+    pub use super::{tok::PushTokensExt, EolError, ErrorKind, StrippedInvisibleError};
     pub use owlchess::moves::{uci, UciMove};
     pub use std::{num::ParseIntError, time::Duration};
     pub use thiserror::Error;
     pub use wurm::prelude::*;
 }
 
-use std::error::Error;
+use std::{error::Error, io};
 
 use thiserror::Error;
 
 use wurm::Warn;
 
+use crate::score::{BoundedRelScore, RelScore};
+
 use super::{
-    msg::{Command, Message},
+    msg::{Command, Direction, Go, Message, UciEvent},
     str::UciString,
-    token::{self, PushTokens, Token},
+    token::{self, PushTokens, Token, WriteTokens},
 };
 
 pub trait Parse {
@@ -47,8 +54,39 @@ pub trait Parse {
         let tokens: Vec<_> = token::tokenize(line).collect();
         Self::parse(&mut &tokens[..], warn)
     }
+
+    /// Same as [`Parse::parse_line`], but first removes the UTF-8 BOM and zero-width characters
+    /// from the edges of each token, warning if any were found
+    ///
+    /// Such characters often appear in copy-pasted lines and prevent the tokens from matching.
+    /// Characters inside the tokens are kept intact, so payloads like `info string` are not
+    /// altered.
+    #[inline]
+    fn parse_line_strip_invisible(line: &str, warn: &mut impl Warn<Self::Err>) -> Option<Self>
+    where
+        Self: Sized,
+        Self::Err: From<StrippedInvisibleError>,
+    {
+        let mut stripped = false;
+        let tokens: Vec<_> = token::tokenize(line)
+            .filter_map(|tok| {
+                let s = tok.as_str().trim_matches(INVISIBLE_CHARS);
+                if s.len() == tok.len() {
+                    return Some(tok);
+                }
+                stripped = true;
+                Token::new(s).ok()
+            })
+            .collect();
+        if stripped {
+            warn.warn(StrippedInvisibleError.into());
+        }
+        Self::parse(&mut &tokens[..], warn)
+    }
 }
 
+const INVISIBLE_CHARS: &[char] = &['\u{feff}', '\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}'];
+
 pub trait Fmt {
     fn fmt(&self, f: &mut impl PushTokens);
 
@@ -58,21 +96,90 @@ pub trait Fmt {
         self.fmt(&mut res);
         res.into()
     }
+
+    /// Appends the tokens to `buf`, separating them from its existing contents with a space
+    #[inline]
+    fn fmt_append(&self, buf: &mut UciString) {
+        self.fmt(buf);
+    }
+
+    /// Writes the formatted line followed by `\n` into `w`, without building an intermediate
+    /// string
+    ///
+    /// `w` receives many small writes (see [`WriteTokens`]), so it should be buffered, e.g. with
+    /// [`io::BufWriter`]. Otherwise, prefer writing the result of [`Fmt::fmt_line`] at once.
+    #[inline]
+    fn write_line(&self, w: &mut impl io::Write) -> io::Result<()> {
+        let mut f = WriteTokens::new(&mut *w);
+        self.fmt(&mut f);
+        f.finish()?;
+        w.write_all(b"\n")
+    }
 }
 
 #[derive(Clone, Debug, Error, Eq, PartialEq)]
 #[error("unexpected end of line")]
 pub struct EolError;
 
-pub use command::Error as CommandError;
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+#[error("stripped BOM or zero-width characters")]
+pub struct StrippedInvisibleError;
+
+/// Broad category of a parse error
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ErrorKind {
+    /// The line is malformed, e.g. has unexpected or missing tokens
+    Syntax,
+    /// The line is well-formed, but the values are invalid or inconsistent
+    Semantic,
+    /// The line ended unexpectedly
+    Eol,
+}
+
+pub use command::{Error as CommandError, SpannedError as SpannedCommandError};
+pub use event::Error as EventError;
 pub use go::Error as GoError;
 pub use info::Error as InfoError;
 pub use message::Error as MessageError;
-pub use movevec::Error as MoveVecError;
+pub use movevec::{parse_with_san as parse_moves_with_san, Error as MoveVecError};
 pub use optbody::Error as OptBodyError;
 pub use score::Error as ScoreError;
+pub use tracker::PositionTracker;
+pub use transcript::Error as TranscriptError;
 pub use tristatus::Error as TriStatusError;
 
+/// Parses the arguments of `go`, also accepting non-standard `k`/`M`/`G` suffixes for `nodes`
+#[inline]
+pub fn parse_go_with_suffixes(tokens: &mut &[&Token], warn: &mut impl Warn<GoError>) -> Go {
+    go::parse_with_suffixes(tokens, warn)
+}
+
+/// Parses the message, also accepting non-standard trailing tokens after `bestmove`
+#[inline]
+pub fn parse_message_trailing_ok(
+    tokens: &mut &[&Token],
+    warn: &mut impl Warn<MessageError>,
+) -> Option<Message> {
+    message::parse_trailing_ok(tokens, warn)
+}
+
+/// Parses `line` as a command or as a message, depending on `dir`
+#[inline]
+pub fn parse_event(
+    dir: Direction,
+    line: &str,
+    warn: &mut impl Warn<EventError>,
+) -> Option<UciEvent> {
+    event::parse(dir, line, warn)
+}
+
+/// Parses a recorded UCI session, where commands sent to the engine are prefixed with `>` and
+/// messages received from the engine are prefixed with `<`
+#[inline]
+pub fn parse_transcript(s: &str, warn: &mut impl Warn<TranscriptError>) -> Vec<UciEvent> {
+    transcript::parse(s, warn)
+}
+
 impl Parse for Command {
     type Err = command::Error;
 
@@ -100,3 +207,75 @@ impl Fmt for Message {
         message::fmt(self, f)
     }
 }
+
+impl Parse for RelScore {
+    type Err = score::Error;
+
+    fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Self::Err>) -> Option<Self> {
+        score::parse_unbounded(tokens, warn)
+    }
+}
+
+impl Fmt for RelScore {
+    fn fmt(&self, f: &mut impl PushTokens) {
+        score::fmt_unbounded(self, f)
+    }
+}
+
+impl Parse for BoundedRelScore {
+    type Err = score::Error;
+
+    fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Self::Err>) -> Option<Self> {
+        score::parse(tokens, warn)
+    }
+}
+
+impl Fmt for BoundedRelScore {
+    fn fmt(&self, f: &mut impl PushTokens) {
+        score::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::msg::Go;
+    use super::*;
+
+    #[test]
+    fn test_write_line() {
+        for cmd in [
+            Command::Uci,
+            Command::Position {
+                startpos: owlchess::RawBoard::from_fen(
+                    "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                )
+                .unwrap(),
+                moves: vec!["e7e5".parse().unwrap()],
+            },
+            Command::Go(Go {
+                searchmoves: Some(Vec::new()),
+                depth: Some(5),
+                ..Default::default()
+            }),
+        ] {
+            let mut buf = Vec::new();
+            cmd.write_line(&mut buf).unwrap();
+            let mut expected = cmd.fmt_line().into_bytes();
+            expected.push(b'\n');
+            assert_eq!(buf, expected);
+        }
+    }
+
+    #[test]
+    fn test_fmt_append() {
+        let mut buf = UciString::new();
+        Command::IsReady.fmt_append(&mut buf);
+        assert_eq!(buf.as_str(), "isready");
+        Command::Go(Go {
+            depth: Some(5),
+            ..Default::default()
+        })
+        .fmt_append(&mut buf);
+        assert_eq!(buf.as_str(), "isready go depth 5");
+    }
+}
@@ -1,3 +1,5 @@
+use owlchess::{Board, Move};
+
 use super::prelude::*;
 
 #[derive(Clone, Debug, Error, Eq, PartialEq)]
@@ -15,6 +17,9 @@ fn looks_like_move(tok: &Token) -> bool {
         && bytes[1].is_ascii_digit()
         && bytes[2].is_ascii_lowercase()
         && bytes[3].is_ascii_digit()
+        && bytes
+            .get(4)
+            .is_none_or(|p| matches!(p, b'q' | b'r' | b'b' | b'n'))
 }
 
 pub fn parse(
@@ -45,6 +50,95 @@ pub fn parse(
     moves
 }
 
+/// Parses a move sequence like the strict parser, but also accepts moves in SAN
+///
+/// SAN moves are resolved against `board` with all the preceding moves applied, and converted to
+/// [`UciMove`]. If some coordinate move cannot be applied, SAN moves after it cannot be resolved
+/// anymore, so the sequence ends on the next SAN move. Parsing stops on the first token which is
+/// neither a coordinate move nor a valid SAN move.
+pub fn parse_with_san(
+    tokens: &mut &[&Token],
+    board: &Board,
+    warn: &mut impl Warn<Error>,
+) -> Vec<UciMove> {
+    let mut moves = Vec::new();
+    let mut board = Some(board.clone());
+    while !tokens.is_empty() {
+        let tok = tokens[0];
+        let mv = if looks_like_move(tok) {
+            match tok.parse::<UciMove>() {
+                Ok(mv) => mv,
+                Err(error) => {
+                    *tokens = &tokens[1..];
+                    warn.warn(Error {
+                        pos: moves.len(),
+                        error,
+                    });
+                    board = None;
+                    continue;
+                }
+            }
+        } else {
+            match board.as_ref().and_then(|b| Move::from_san(tok, b).ok()) {
+                Some(mv) => mv.uci(),
+                None => break,
+            }
+        };
+        *tokens = &tokens[1..];
+        board = board.and_then(|b| b.make_move(mv).ok());
+        moves.push(mv);
+    }
+    moves
+}
+
 pub fn fmt(src: &[UciMove], f: &mut impl PushTokens) {
     src.iter().for_each(|mv| f.push_fmt(mv));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::token;
+    use super::*;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_with_san() {
+        let tokens: Vec<_> = token::tokenize("e4 e7e5 Nf3 Nc6 Bb5+ depth 3").collect();
+        let mut tokens = &tokens[..];
+        let mut warn = CollectAll::default();
+        let moves = parse_with_san(&mut tokens, &Board::initial(), &mut warn);
+        let expected: Vec<UciMove> = ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(moves, expected);
+        assert!(warn.0.is_empty());
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0], "depth");
+    }
+
+    #[test]
+    fn test_strict_rejects_san() {
+        let tokens: Vec<_> = token::tokenize("e2e4 e5 g1f3").collect();
+        let mut tokens = &tokens[..];
+        let mut warn = CollectAll::default();
+        let moves = parse(&mut tokens, true, &mut warn);
+        assert_eq!(moves, vec!["e2e4".parse().unwrap()]);
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn test_promote() {
+        let tokens: Vec<_> = token::tokenize("a2a1n e7e8q e7e8k d2d4").collect();
+        let mut tokens = &tokens[..];
+        let mut warn = CollectAll::default();
+        let moves = parse(&mut tokens, false, &mut warn);
+        assert_eq!(
+            moves,
+            vec!["a2a1n".parse().unwrap(), "e7e8q".parse().unwrap()]
+        );
+        assert!(warn.0.is_empty());
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0], "e7e8k");
+    }
+}
@@ -88,9 +88,7 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<OptB
             None
         }
     })();
-    if !tokens.is_empty() {
-        warn.warn(Error::ExtraToken(tokens[0].to_string()));
-    }
+    tok::warn_extra(tokens, Error::ExtraToken, warn);
     result
 }
 
@@ -123,3 +121,55 @@ pub fn fmt(src: &OptBody, f: &mut impl PushTokens) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::token;
+    use super::*;
+    use std::str::FromStr;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_combo_case_preserved() {
+        let line = "combo default Solid var solid var Normal var RISKY Play";
+        let tokens: Vec<_> = token::tokenize(line).collect();
+        let mut warn = CollectAll::default();
+        let body = parse(&mut &tokens[..], &mut warn).unwrap();
+        assert!(warn.0.is_empty());
+
+        let OptBody::Combo { default, vars } = &body else {
+            panic!("expected combo, got {:?}", body);
+        };
+        assert_eq!(default.as_str(), "Solid");
+        assert_eq!(default, &OptComboVar::from_str("SOLID").unwrap());
+        let vars: Vec<_> = vars.iter().map(|var| var.as_str()).collect();
+        assert_eq!(vars, vec!["solid", "Normal", "RISKY Play"]);
+
+        let mut formatted = UciString::new();
+        fmt(&body, &mut formatted);
+        assert_eq!(formatted.as_str(), line);
+    }
+
+    #[test]
+    fn test_spin_extremes() {
+        for body in [
+            OptBody::Spin {
+                default: i64::MIN,
+                min: i64::MIN,
+                max: i64::MAX,
+            },
+            OptBody::Spin {
+                default: i64::MAX,
+                min: i64::MIN,
+                max: i64::MAX,
+            },
+        ] {
+            let mut formatted = UciString::new();
+            fmt(&body, &mut formatted);
+            let tokens: Vec<_> = token::tokenize(formatted.as_str()).collect();
+            let mut warn = CollectAll::default();
+            assert_eq!(parse(&mut &tokens[..], &mut warn), Some(body));
+            assert!(warn.0.is_empty());
+        }
+    }
+}
@@ -1,5 +1,7 @@
+use owlchess::RawBoard;
+
 use super::{prelude::*, tok};
-use crate::score::{Bound, BoundedRelScore, RelScore};
+use crate::score::{self, Bound, BoundedAbsScore, BoundedRelScore, RelScore};
 
 #[derive(Clone, Debug, Error, Eq, PartialEq)]
 #[non_exhaustive]
@@ -8,19 +10,31 @@ pub enum Error {
     UnexpectedToken(String),
     #[error(transparent)]
     UnexpectedEol(#[from] EolError),
-    #[error("cannot parse integer: {0}")]
+    #[error("cannot parse integer (expected decimal): {0}")]
     BadInteger(#[from] ParseIntError),
     #[error("mate distance {0} is too large to fit into constraints")]
     MateTooLarge(i64),
+    #[error("no value after \"{0}\"")]
+    MissingScoreValue(&'static str),
 }
 
-fn parse_unbounded(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<RelScore> {
+fn expect_value(tokens: &[&Token], kind: &'static str, warn: &mut impl Warn<Error>) -> Option<()> {
+    if tokens.is_empty() {
+        warn.warn(Error::MissingScoreValue(kind));
+        return None;
+    }
+    Some(())
+}
+
+pub fn parse_unbounded(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<RelScore> {
     match tok::next_warn(tokens, warn)?.as_str() {
         "cp" => {
+            expect_value(tokens, "cp", warn)?;
             let value = tok::parse(tokens, warn)?;
             Some(RelScore::Cp(value))
         }
         "mate" => {
+            expect_value(tokens, "mate", warn)?;
             let src: i64 = tok::parse(tokens, warn)?;
             let moves = src
                 .abs()
@@ -55,7 +69,20 @@ pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<Boun
     Some(BoundedRelScore { score, bound })
 }
 
-fn fmt_unbounded(src: &RelScore, f: &mut impl PushTokens) {
+impl BoundedAbsScore {
+    /// Parses the score reported by the engine (i.e. the tokens after `score`) and converts it into
+    /// the absolute one, using the side to move from `board`
+    #[inline]
+    pub fn from_rel_tokens(
+        tokens: &mut &[&Token],
+        board: &RawBoard,
+        warn: &mut impl Warn<Error>,
+    ) -> Option<Self> {
+        parse(tokens, warn).map(|score| score::score_abs_from_position(score, board))
+    }
+}
+
+pub fn fmt_unbounded(src: &RelScore, f: &mut impl PushTokens) {
     match src {
         RelScore::Cp(val) => f.push_tag("cp", val),
         RelScore::Mate { moves, win } => {
@@ -76,3 +103,66 @@ pub fn fmt(src: &BoundedRelScore, f: &mut impl PushTokens) {
         Bound::Exact => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::token;
+    use super::*;
+    use crate::score::AbsScore;
+    use owlchess::Color;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_from_rel_tokens() {
+        let board =
+            RawBoard::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+                .unwrap();
+        let tokens: Vec<_> = token::tokenize("mate 3 lowerbound").collect();
+        let mut tokens = &tokens[..];
+        let mut warn = CollectAll::default();
+        let score = BoundedAbsScore::from_rel_tokens(&mut tokens, &board, &mut warn);
+        assert_eq!(
+            score,
+            Some(BoundedAbsScore {
+                score: AbsScore::Mate {
+                    moves: 3,
+                    winner: Color::Black,
+                },
+                bound: Bound::Upper,
+            })
+        );
+        assert!(tokens.is_empty());
+        assert!(warn.0.is_empty());
+    }
+
+    #[test]
+    fn test_missing_value() {
+        for (line, kind) in [("cp", "cp"), ("mate", "mate")] {
+            let tokens: Vec<_> = token::tokenize(line).collect();
+            let mut warn = CollectAll::default();
+            assert_eq!(parse(&mut &tokens[..], &mut warn), None);
+            assert_eq!(warn.0, vec![Error::MissingScoreValue(kind)]);
+        }
+    }
+
+    #[test]
+    fn test_cp_forms() {
+        for (line, value) in [("cp +007", 7), ("cp -0", 0), ("cp -015", -15)] {
+            let tokens: Vec<_> = token::tokenize(line).collect();
+            let mut warn = CollectAll::default();
+            assert_eq!(
+                parse(&mut &tokens[..], &mut warn),
+                Some(BoundedRelScore {
+                    score: RelScore::Cp(value),
+                    bound: Bound::Exact,
+                })
+            );
+            assert!(warn.0.is_empty());
+        }
+
+        let tokens: Vec<_> = token::tokenize("cp 0x10").collect();
+        let mut warn = CollectAll::default();
+        assert_eq!(parse(&mut &tokens[..], &mut warn), None);
+        assert!(matches!(warn.0.as_slice(), [Error::BadInteger(_)]));
+    }
+}
@@ -0,0 +1,82 @@
+//! Helpers to (de)serialize values as their UCI representation
+//!
+//! Enabled with `serde` feature.
+
+/// Serializes the value as a string formatted via [`Fmt`](super::Fmt), and deserializes it back
+/// via [`Parse`](super::Parse)
+///
+/// Use it as `#[serde(with = "owlengine::uci::parse::serde::as_uci_string")]`, for example, to
+/// represent scores as `"cp 31"` or `"mate -3 lowerbound"` instead of structs. Deserialization
+/// fails on any parse warning or extra tokens.
+pub mod as_uci_string {
+    use std::borrow::Cow;
+
+    use ::serde::{de, Deserialize, Deserializer, Serializer};
+
+    use super::super::{
+        super::{sink::TryFirst, token},
+        Fmt, Parse,
+    };
+
+    pub fn serialize<T: Fmt, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.fmt_line())
+    }
+
+    pub fn deserialize<'de, T: Parse, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        let src = Cow::<'de, str>::deserialize(deserializer)?;
+        let tokens: Vec<_> = token::tokenize(&src).collect();
+        let mut tokens = &tokens[..];
+        let mut warn = TryFirst::new();
+        let value = T::parse(&mut tokens, &mut warn);
+        let value = warn.into_result(value).map_err(de::Error::custom)?;
+        if let Some(tok) = tokens.first() {
+            return Err(de::Error::custom(format_args!(
+                "extra token: {}",
+                tok.as_str()
+            )));
+        }
+        value.ok_or_else(|| de::Error::custom("no value"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::serde::{Deserialize, Serialize};
+
+    use crate::score::{Bound, BoundedRelScore, RelScore};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Entry {
+        #[serde(with = "super::as_uci_string")]
+        score: BoundedRelScore,
+        #[serde(with = "super::as_uci_string")]
+        best: RelScore,
+    }
+
+    #[test]
+    fn test_as_uci_string() {
+        let entry = Entry {
+            score: BoundedRelScore {
+                score: RelScore::Mate {
+                    moves: 3,
+                    win: false,
+                },
+                bound: Bound::Lower,
+            },
+            best: RelScore::Cp(31),
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(json, r#"{"score":"mate -3 lowerbound","best":"cp 31"}"#);
+        assert_eq!(serde_json::from_str::<Entry>(&json).unwrap(), entry);
+
+        for bad in [
+            r#"{"score":"cp","best":"cp 31"}"#,
+            r#"{"score":"cp 31","best":"cp 31 lowerbound"}"#,
+            r#"{"score":"","best":"cp 31"}"#,
+        ] {
+            assert!(serde_json::from_str::<Entry>(bad).is_err());
+        }
+    }
+}
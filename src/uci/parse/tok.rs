@@ -70,6 +70,16 @@ pub fn expect<E: From<EolError> + Error>(
     Some(())
 }
 
+pub fn warn_extra<E: Error>(
+    tokens: &[&Token],
+    func: impl Fn(String) -> E,
+    warn: &mut impl Warn<E>,
+) {
+    for tok in tokens {
+        warn.warn(func(tok.to_string()));
+    }
+}
+
 struct Kw(&'static str);
 
 unsafe impl TokenSafe for Kw {}
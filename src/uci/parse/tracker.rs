@@ -0,0 +1,137 @@
+use owlchess::RawBoard;
+
+use super::super::{msg::Command, token};
+use super::{command::Error, prelude::*, Parse};
+
+/// Parser for a stream of `position` commands, which reuses the previous result when the new
+/// command only appends moves to the previous one
+///
+/// When a GUI plays a game, each `position` command usually repeats the previous one with one
+/// more move added. Parsing each of them from scratch takes quadratic time over the whole game,
+/// while the tracker parses only the appended moves. If the new command is not a continuation of
+/// the previous one, it falls back to the full parse.
+#[derive(Clone, Debug, Default)]
+pub struct PositionTracker {
+    line: String,
+    has_moves: bool,
+    valid: bool,
+    startpos: RawBoard,
+    moves: Vec<UciMove>,
+}
+
+impl PositionTracker {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `line`, which must contain a `position` command
+    ///
+    /// Returns the starting position and the moves if the command was parsed successfully. If
+    /// `line` contains some other command, `None` is returned.
+    pub fn update(
+        &mut self,
+        line: &str,
+        warn: &mut impl Warn<Error>,
+    ) -> Option<(&RawBoard, &[UciMove])> {
+        let appended = line
+            .strip_prefix(self.line.as_str())
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            .filter(|_| self.valid && self.has_moves);
+        match appended {
+            Some(rest) => self.update_incremental(rest, warn),
+            None => self.update_full(line, warn),
+        }
+        if !self.valid {
+            return None;
+        }
+        self.line.clear();
+        self.line.push_str(line);
+        Some((&self.startpos, &self.moves))
+    }
+
+    fn update_incremental(&mut self, rest: &str, warn: &mut impl Warn<Error>) {
+        for tok in token::tokenize(rest) {
+            match tok.parse() {
+                Ok(mv) => self.moves.push(mv),
+                Err(error) => {
+                    let pos = self.moves.len();
+                    warn.warn(Error::InvalidMove { pos, error });
+                    self.valid = false;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn update_full(&mut self, line: &str, warn: &mut impl Warn<Error>) {
+        self.valid = false;
+        if let Some(Command::Position { startpos, moves }) = Command::parse_line(line, warn) {
+            self.has_moves = token::tokenize(line).any(|tok| tok == "moves");
+            self.valid = true;
+            self.startpos = startpos;
+            self.moves = moves;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wurm::CollectAll;
+
+    const GAME: &[&str] = &[
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4", "g8f6", "e1g1", "f8e7",
+    ];
+
+    #[test]
+    fn test_incremental() {
+        let mut tracker = PositionTracker::new();
+        let mut warn = CollectAll::default();
+        let mut line = "position startpos moves".to_string();
+        for (i, mv) in GAME.iter().enumerate() {
+            line += " ";
+            line += mv;
+            let (startpos, moves) = tracker.update(&line, &mut warn).unwrap();
+            assert_eq!(startpos, &RawBoard::initial());
+            assert_eq!(moves.len(), i + 1);
+            assert_eq!(moves.last().unwrap(), &mv.parse().unwrap());
+        }
+        assert!(warn.0.is_empty());
+    }
+
+    #[test]
+    fn test_fallback() {
+        let mut tracker = PositionTracker::new();
+        let mut warn = CollectAll::default();
+        tracker
+            .update("position startpos moves e2e4 e7e5", &mut warn)
+            .unwrap();
+
+        let (_, moves) = tracker
+            .update("position startpos moves d2d4", &mut warn)
+            .unwrap();
+        assert_eq!(moves, &["d2d4".parse().unwrap()]);
+
+        assert!(tracker
+            .update("position startpos moves d2d4 xyz", &mut warn)
+            .is_none());
+        assert_eq!(warn.0.len(), 1);
+
+        let fen = "position fen 8/P7/8/8/8/8/8/k6K w - - 0 1 moves";
+        let (startpos, moves) = tracker.update(fen, &mut warn).unwrap();
+        assert_eq!(
+            startpos,
+            &RawBoard::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap()
+        );
+        assert!(moves.is_empty());
+
+        let (_, moves) = tracker
+            .update(&format!("{} a7a8q", fen), &mut warn)
+            .unwrap();
+        assert_eq!(moves, &["a7a8q".parse().unwrap()]);
+
+        assert!(tracker.update("isready", &mut warn).is_none());
+        assert_eq!(warn.0.len(), 1);
+    }
+}
@@ -0,0 +1,109 @@
+use super::super::{
+    msg::{Command, Message, UciEvent},
+    token,
+};
+use super::{command, message, prelude::*, Parse};
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("line #{}: no direction prefix, skipping", pos + 1)]
+    NoPrefix { pos: usize },
+    #[error("line #{}: cannot parse command: {}", pos + 1, error)]
+    BadCommand {
+        pos: usize,
+        #[source]
+        error: command::Error,
+    },
+    #[error("line #{}: cannot parse message: {}", pos + 1, error)]
+    BadMessage {
+        pos: usize,
+        #[source]
+        error: message::Error,
+    },
+}
+
+pub fn parse(src: &str, warn: &mut impl Warn<Error>) -> Vec<UciEvent> {
+    let mut result = Vec::new();
+    for (pos, line) in token::split_lines(src).enumerate() {
+        let line = line.trim_start();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(line) = line.strip_prefix('>') {
+            let cmd = Command::parse_line(
+                line,
+                &mut warn.adapt_map(|error| Error::BadCommand { pos, error }),
+            );
+            result.extend(cmd.map(UciEvent::ToEngine));
+        } else if let Some(line) = line.strip_prefix('<') {
+            let msg = Message::parse_line(
+                line,
+                &mut warn.adapt_map(|error| Error::BadMessage { pos, error }),
+            );
+            result.extend(msg.map(UciEvent::FromEngine));
+        } else {
+            warn.warn(Error::NoPrefix { pos });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::msg::{Go, Id};
+    use super::*;
+    use wurm::CollectAll;
+
+    #[test]
+    fn test_session() {
+        let src = "\
+> uci
+< id name Stockfish 15
+< uciok
+garbage line
+
+> isready
+< readyok
+> go depth 1
+< bestmove e2e4
+";
+        let mut warn = CollectAll::default();
+        let lines = parse(src, &mut warn);
+        assert_eq!(warn.0, vec![Error::NoPrefix { pos: 3 }]);
+        assert_eq!(
+            lines,
+            vec![
+                UciEvent::ToEngine(Command::Uci),
+                UciEvent::FromEngine(Message::Id(Id::Name(UciString::from("Stockfish 15")))),
+                UciEvent::FromEngine(Message::UciOk),
+                UciEvent::ToEngine(Command::IsReady),
+                UciEvent::FromEngine(Message::ReadyOk),
+                UciEvent::ToEngine(Command::Go(Go {
+                    depth: Some(1),
+                    ..Default::default()
+                })),
+                UciEvent::FromEngine(Message::BestMove {
+                    bestmove: "e2e4".parse().unwrap(),
+                    ponder: None,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let mut warn = CollectAll::default();
+        let lines = parse("> uci\r< uciok\r\n> isready\n< readyok\r", &mut warn);
+        assert!(warn.0.is_empty());
+        assert_eq!(
+            lines,
+            vec![
+                UciEvent::ToEngine(Command::Uci),
+                UciEvent::FromEngine(Message::UciOk),
+                UciEvent::ToEngine(Command::IsReady),
+                UciEvent::FromEngine(Message::ReadyOk),
+            ]
+        );
+    }
+}
@@ -11,11 +11,12 @@ pub enum Error {
 }
 
 pub fn parse(tokens: &mut &[&Token], warn: &mut impl Warn<Error>) -> Option<TriStatus> {
-    match tok::next_warn(tokens, warn)?.as_str() {
+    let tok = tok::next_warn(tokens, warn)?;
+    match tok.to_ascii_lowercase().as_str() {
         "ok" => Some(TriStatus::Ok),
         "checking" => Some(TriStatus::Checking),
         "error" => Some(TriStatus::Error),
-        tok => {
+        _ => {
             warn.warn(Error::UnexpectedToken(tok.to_string()));
             None
         }
@@ -29,3 +30,30 @@ pub fn fmt(src: &TriStatus, f: &mut impl PushTokens) {
         TriStatus::Error => f.push_kw("error"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::token;
+    use super::*;
+    use wurm::CollectAll;
+
+    fn parse_str(s: &str) -> (Option<TriStatus>, Vec<Error>) {
+        let tokens: Vec<_> = token::tokenize(s).collect();
+        let mut warn = CollectAll::default();
+        let status = parse(&mut &tokens[..], &mut warn);
+        (status, warn.0)
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        for s in ["ok", "OK", "Ok", "oK"] {
+            assert_eq!(parse_str(s), (Some(TriStatus::Ok), vec![]));
+        }
+        assert_eq!(parse_str("Checking"), (Some(TriStatus::Checking), vec![]));
+        assert_eq!(parse_str("ERROR"), (Some(TriStatus::Error), vec![]));
+        assert_eq!(
+            parse_str("Fine"),
+            (None, vec![Error::UnexpectedToken("Fine".to_string())])
+        );
+    }
+}
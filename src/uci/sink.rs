@@ -0,0 +1,196 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    error::Error,
+    mem::{self, Discriminant},
+};
+
+use wurm::Warn;
+
+use super::parse::{EolError, Parse};
+
+/// Sink which collects statistics about the parsed lines and the warnings
+///
+/// Warnings are bucketed by their enum variant. For each variant, the first warning is kept as a
+/// sample to show in the summary.
+#[derive(Debug, Clone)]
+pub struct ParseStats<E> {
+    lines: usize,
+    warnings: usize,
+    buckets: HashMap<Discriminant<E>, (E, usize)>,
+}
+
+impl<E> Default for ParseStats<E> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            lines: 0,
+            warnings: 0,
+            buckets: HashMap::new(),
+        }
+    }
+}
+
+impl<E: Error> ParseStats<E> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `line`, counting it and all the warnings it produces
+    #[inline]
+    pub fn parse_line<P: Parse<Err = E>>(&mut self, line: &str) -> Option<P> {
+        self.lines += 1;
+        P::parse_line(line, self)
+    }
+
+    #[inline]
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    #[inline]
+    pub fn warnings(&self) -> usize {
+        self.warnings
+    }
+
+    /// Returns the number of warnings with the same variant as `error`
+    #[inline]
+    pub fn count(&self, error: &E) -> usize {
+        self.buckets
+            .get(&mem::discriminant(error))
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Returns a sample warning and the number of warnings for each variant, most frequent first
+    pub fn summary(&self) -> Vec<(&E, usize)> {
+        let mut result: Vec<_> = self
+            .buckets
+            .values()
+            .map(|(sample, count)| (sample, *count))
+            .collect();
+        result.sort_by_key(|(sample, count)| (Reverse(*count), sample.to_string()));
+        result
+    }
+}
+
+impl<E: Error> Warn<E> for ParseStats<E> {
+    fn warn(&mut self, error: E) {
+        self.warnings += 1;
+        self.buckets
+            .entry(mem::discriminant(&error))
+            .or_insert((error, 0))
+            .1 += 1;
+    }
+}
+
+/// Sink which keeps the first warning and allows to turn it into an `Err`
+///
+/// Unlike aborting on the first warning, the parser still runs to the end, so the value is always
+/// produced, and it's up to the caller whether to keep it (see [`TryFirst::into_result`]).
+#[derive(Debug, Clone)]
+pub struct TryFirst<E>(pub Option<E>);
+
+impl<E> Default for TryFirst<E> {
+    #[inline]
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<E: Error> TryFirst<E> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `Err` with the first warning if there were any, and `Ok(value)` otherwise
+    #[inline]
+    pub fn into_result<T>(self, value: T) -> Result<T, E> {
+        match self.0 {
+            Some(err) => Err(err),
+            None => Ok(value),
+        }
+    }
+}
+
+impl<E: Error> Warn<E> for TryFirst<E> {
+    #[inline]
+    fn warn(&mut self, error: E) {
+        if self.0.is_none() {
+            self.0 = Some(error);
+        }
+    }
+}
+
+/// Parses `line`, returning the first warning as `Err`
+///
+/// If the parser produced neither a value nor a warning (e.g. on an empty line), returns
+/// [`EolError`] converted into `P::Err`.
+#[inline]
+pub fn parse_or_first_error<P: Parse>(line: &str) -> Result<P, P::Err>
+where
+    P::Err: From<EolError>,
+{
+    let mut warn = TryFirst::new();
+    let value = P::parse_line(line, &mut warn);
+    warn.into_result(value)?.ok_or_else(|| EolError.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{msg::Command, parse::CommandError};
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut stats = ParseStats::new();
+        for line in [
+            "uci",
+            "isready foo bar",
+            "frobnicate",
+            "position startpos",
+            "stop baz",
+        ] {
+            let _: Option<Command> = stats.parse_line(line);
+        }
+        assert_eq!(stats.lines(), 5);
+        assert_eq!(stats.warnings(), 5);
+        assert_eq!(stats.count(&CommandError::ExtraToken(String::new())), 3);
+        assert_eq!(
+            stats.count(&CommandError::UnexpectedToken(String::new())),
+            1
+        );
+        assert_eq!(stats.count(&CommandError::PositionNoMoves), 1);
+        assert_eq!(stats.count(&CommandError::NoPosition), 0);
+
+        let summary = stats.summary();
+        assert_eq!(summary.len(), 3);
+        assert_eq!(
+            summary[0],
+            (&CommandError::ExtraToken("foo".to_string()), 3)
+        );
+    }
+
+    #[test]
+    fn test_try_first() {
+        assert_eq!(parse_or_first_error("uci"), Ok(Command::Uci));
+        assert_eq!(
+            parse_or_first_error::<Command>(""),
+            Err(CommandError::UnexpectedEol(EolError))
+        );
+        assert_eq!(
+            parse_or_first_error::<Command>("isready foo bar"),
+            Err(CommandError::ExtraToken("foo".to_string()))
+        );
+
+        // The parser still runs to the end and produces the value
+        let mut warn = TryFirst::new();
+        let value = Command::parse_line("isready foo bar", &mut warn);
+        assert_eq!(value, Some(Command::IsReady));
+        assert_eq!(
+            warn.into_result(value),
+            Err(CommandError::ExtraToken("foo".to_string()))
+        );
+    }
+}
@@ -23,6 +23,23 @@ macro_rules! impl_uci_str_base {
 
         unsafe impl MultiTokenSafe for $name {}
 
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            #[inline]
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        /// Deserializes from a string, validating and normalizing it like [`FromStr`] does
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+                s.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
         impl From<$name> for String {
             #[inline]
             fn from(val: $name) -> Self {
@@ -50,6 +67,9 @@ macro_rules! impl_uci_str_base {
                 Self::default()
             }
 
+            /// Returns the string as is, in its original casing
+            ///
+            /// For the types compared case-insensitively, this is the form to show to the user.
             #[inline]
             pub fn as_str(&self) -> &str {
                 self.0.as_str()
@@ -240,6 +260,26 @@ impl UciString {
         Self(tokens.join(" "))
     }
 
+    /// Creates a string from the tokens emitted by `value`
+    #[inline]
+    pub fn from_display<T: MultiTokenSafe>(value: &T) -> Self {
+        let mut res = Self::new();
+        res.push_many_fmt(value);
+        res
+    }
+
+    /// Returns `true` if the string survives formatting and parsing back in every context
+    ///
+    /// Keywords inside `UciString` are harmless, as it always comes last in the line (like in
+    /// `setoption ... value <string>` or `info string <string>`), or after the first occurrence of
+    /// the separating keyword, which is forbidden in the preceding names (see [`OptName`] and
+    /// [`RegisterName`]). The only exception is the literal `<empty>`, which denotes an empty
+    /// default value in `option ... type string`, so it cannot be represented there.
+    #[inline]
+    pub fn is_roundtrip_safe(&self) -> bool {
+        self.as_str() != "<empty>"
+    }
+
     #[inline]
     fn maybe_push_space(&mut self) {
         if !self.0.is_empty() {
@@ -279,12 +319,20 @@ pub struct RegisterName(String);
 impl_uci_str! {RegisterName, &["code"]}
 impl_case_sensitive! {RegisterName}
 
+/// Option name
+///
+/// Names are compared case-insensitively, but the original casing is kept, and it is returned by
+/// [`OptName::as_str`] and used in formatting.
 #[derive(Debug, Clone, Default)]
 pub struct OptName(String);
 
 impl_uci_str! {OptName, &["type", "value"]}
 impl_case_insensitive! {OptName}
 
+/// Variant of a combo option
+///
+/// Variants are compared case-insensitively, but the original casing is kept, and it is returned
+/// by [`OptComboVar::as_str`] and used in formatting.
 #[derive(Debug, Clone, Default)]
 pub struct OptComboVar(String);
 
@@ -307,3 +355,78 @@ fn from_str_impl(value: &str, bad_tokens: &[&'static str]) -> Result<String, Err
     }
     Ok(s)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_tokens() {
+        assert_eq!(
+            OptName::from_str("Hash type value"),
+            Err(Error::BadToken("type"))
+        );
+        assert_eq!(
+            OptName::from_str("Skill value"),
+            Err(Error::BadToken("value"))
+        );
+        assert_eq!(
+            RegisterName::from_str("John code"),
+            Err(Error::BadToken("code"))
+        );
+        assert_eq!(
+            OptComboVar::from_str("a var b"),
+            Err(Error::BadToken("var"))
+        );
+        assert!(OptName::from_str("Types Values").is_ok());
+    }
+
+    #[test]
+    fn test_roundtrip_safe() {
+        assert!(UciString::from("value type code var name").is_roundtrip_safe());
+        assert!(UciString::from("").is_roundtrip_safe());
+        assert!(UciString::from("<empty> string").is_roundtrip_safe());
+        assert!(!UciString::from("<empty>").is_roundtrip_safe());
+    }
+
+    #[test]
+    fn test_from_display() {
+        let board = owlchess::Board::initial();
+        let mv = owlchess::Move::from_uci("g1f3", &board).unwrap();
+        let mut s = UciString::from_display(&mv);
+        assert_eq!(s.as_str(), "g1f3");
+        s.push_many_fmt(&board);
+        assert_eq!(
+            s.as_str(),
+            "g1f3 rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(UciString::from_display(&42_u32).as_str(), "42");
+    }
+
+    #[test]
+    fn test_case_insensitive_as_str() {
+        let a: OptName = "Skill Level".parse().unwrap();
+        let b: OptName = "skill level".parse().unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.as_str(), "Skill Level");
+        assert_eq!(b.as_str(), "skill level");
+        assert_eq!(a.to_string(), "Skill Level");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let name: OptName = serde_json::from_str(r#""Skill  Level""#).unwrap();
+        assert_eq!(name.as_str(), "Skill Level");
+        assert_eq!(serde_json::to_string(&name).unwrap(), r#""Skill Level""#);
+
+        let err = serde_json::from_str::<OptName>(r#""Hash type spin""#).unwrap_err();
+        assert!(err.to_string().contains("type"));
+        assert!(serde_json::from_str::<RegisterName>(r#""John code""#).is_err());
+        assert!(serde_json::from_str::<OptComboVar>(r#""a var b""#).is_err());
+
+        let s: UciString = serde_json::from_str(r#""  type\tvar  ""#).unwrap();
+        assert_eq!(s.as_str(), "type var");
+        assert!(serde_json::from_str::<UciString>("42").is_err());
+    }
+}
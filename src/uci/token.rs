@@ -1,4 +1,10 @@
-use std::{borrow::Borrow, fmt::Display, num, ops::Deref};
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    io::{self, Write},
+    num,
+    ops::Deref,
+};
 
 use owlchess::{moves::UciMove, Board, Move, RawBoard};
 
@@ -17,6 +23,9 @@ pub enum Error {
 pub struct Token(str);
 
 impl Token {
+    /// # Safety
+    ///
+    /// `s` must be non-empty and must not contain whitespace.
     #[inline]
     pub unsafe fn new_unchecked(s: &str) -> &Token {
         &*(s as *const str as *const Token)
@@ -96,6 +105,42 @@ pub fn tokenize(s: &str) -> impl Iterator<Item = &Token> {
         .map(|tok| unsafe { Token::new_unchecked(tok) })
 }
 
+/// Validates each string as a token, returning the position of the first invalid one along with
+/// the error
+pub fn tokens_from_strs<'a>(
+    it: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<&'a Token>, (usize, Error)> {
+    it.into_iter()
+        .enumerate()
+        .map(|(pos, s)| Token::new(s).map_err(|err| (pos, err)))
+        .collect()
+}
+
+/// Splits `s` into lines, accepting `\n`, `\r\n` and lone `\r` as line terminators
+///
+/// Like [`str::lines`], the final line terminator is optional and doesn't produce an empty line.
+pub fn split_lines(s: &str) -> impl Iterator<Item = &str> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        match rest.find(['\r', '\n']) {
+            Some(pos) => {
+                let line = &rest[..pos];
+                let skip = if rest[pos..].starts_with("\r\n") {
+                    2
+                } else {
+                    1
+                };
+                rest = &rest[pos + skip..];
+                Some(line)
+            }
+            None => Some(std::mem::take(&mut rest)),
+        }
+    })
+}
+
 macro_rules! token_safe {
     ($($ty:ty),* $(,)?) => {
         $(
@@ -104,7 +149,16 @@ macro_rules! token_safe {
     };
 }
 
+/// # Safety
+///
+/// The [`Display`] implementation must always emit exactly one token, i.e. a non-empty string
+/// without whitespace.
 pub unsafe trait TokenSafe: Display {}
+
+/// # Safety
+///
+/// The [`Display`] implementation must emit zero or more tokens separated by single spaces,
+/// without leading or trailing whitespace.
 pub unsafe trait MultiTokenSafe: Display {}
 
 token_safe! {
@@ -132,3 +186,120 @@ pub trait PushTokens {
         }
     }
 }
+
+/// Writes the tokens directly into [`io::Write`], without building an intermediate string
+///
+/// Since [`PushTokens`] methods cannot fail, the first I/O error is remembered and returned from
+/// [`WriteTokens::finish`]. All the tokens pushed after the error are ignored.
+///
+/// The writer is not buffered: each token and each separator results in a separate call to
+/// [`Write::write`]. When writing to a file or a socket, wrap it into [`io::BufWriter`] first.
+#[derive(Debug)]
+pub struct WriteTokens<W> {
+    inner: W,
+    empty: bool,
+    error: Option<io::Error>,
+}
+
+struct SepWriter<'a, W> {
+    inner: &'a mut W,
+    sep: bool,
+    written: bool,
+}
+
+impl<W: Write> Write for SepWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.sep && !self.written {
+            self.inner.write_all(b" ")?;
+        }
+        self.written = true;
+        self.inner.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> WriteTokens<W> {
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            empty: true,
+            error: None,
+        }
+    }
+
+    #[inline]
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.inner),
+        }
+    }
+
+    fn write_value(&mut self, value: &dyn Display) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut w = SepWriter {
+            inner: &mut self.inner,
+            sep: !self.empty,
+            written: false,
+        };
+        if let Err(err) = write!(w, "{}", value) {
+            self.error = Some(err);
+        }
+        if w.written {
+            self.empty = false;
+        }
+    }
+}
+
+impl<W: Write> PushTokens for WriteTokens<W> {
+    #[inline]
+    fn push(&mut self, token: &Token) {
+        self.write_value(&token.as_str());
+    }
+
+    #[inline]
+    fn push_fmt<T: TokenSafe>(&mut self, value: &T) {
+        self.write_value(value);
+    }
+
+    #[inline]
+    fn push_many_fmt<T: MultiTokenSafe>(&mut self, value: &T) {
+        self.write_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_from_strs() {
+        let tokens = tokens_from_strs(["go", "depth", "10"]).unwrap();
+        assert_eq!(tokens, vec!["go", "depth", "10"]);
+        assert_eq!(tokens_from_strs([]), Ok(vec![]));
+        assert_eq!(
+            tokens_from_strs(["go", "depth 10", ""]),
+            Err((1, Error::Whitespace))
+        );
+        assert_eq!(tokens_from_strs(["go", ""]), Err((1, Error::Empty)));
+    }
+
+    #[test]
+    fn test_split_lines() {
+        let lines: Vec<_> = split_lines("uci\nisready\r\nstop\rquit\r\rgo\n").collect();
+        assert_eq!(lines, vec!["uci", "isready", "stop", "quit", "", "go"]);
+        assert_eq!(split_lines("").count(), 0);
+        assert_eq!(split_lines("uci").collect::<Vec<_>>(), vec!["uci"]);
+        assert_eq!(split_lines("\r\n").collect::<Vec<_>>(), vec![""]);
+    }
+}
@@ -12,6 +12,9 @@ pub enum PermilleError {
 }
 
 impl Permille {
+    pub const EMPTY: Self = Self(0);
+    pub const FULL: Self = Self(1000);
+
     #[inline]
     pub fn new(amount: u16) -> Self {
         assert!(amount <= 1000, "permille amount must be between 0 and 1000");
@@ -27,6 +30,29 @@ impl Permille {
     pub fn amount(&self) -> u16 {
         self.0
     }
+
+    /// Returns `true` if `self` is strictly greater than `other`
+    #[inline]
+    pub fn is_above(&self, other: Permille) -> bool {
+        *self > other
+    }
+
+    /// Renders a progress bar of `width` cells, where filled cells are `#` and empty ones are `-`
+    ///
+    /// The number of filled cells is rounded to the nearest integer.
+    pub fn bar(&self, width: usize) -> String {
+        let filled = self.filled_cells(width);
+        let mut result = "#".repeat(filled);
+        result.push_str(&"-".repeat(width - filled));
+        result
+    }
+
+    #[inline]
+    fn filled_cells(&self, width: usize) -> usize {
+        // Computed in `u128`, so that large `width` doesn't overflow. The result doesn't exceed
+        // `width`, thus it always fits into `usize`.
+        ((self.0 as u128 * width as u128 + 500) / 1000) as usize
+    }
 }
 
 impl From<Permille> for f32 {
@@ -69,3 +95,38 @@ pub enum TriStatus {
     Checking,
     Error,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(Permille::new(0).bar(10), "----------");
+        assert_eq!(Permille::new(500).bar(10), "#####-----");
+        assert_eq!(Permille::new(1000).bar(10), "##########");
+        assert_eq!(Permille::new(949).bar(10), "#########-");
+        assert_eq!(Permille::new(950).bar(10), "##########");
+        assert_eq!(Permille::new(500).bar(0), "");
+
+        assert_eq!(Permille::new(1000).filled_cells(usize::MAX), usize::MAX);
+        assert_eq!(
+            Permille::new(500).filled_cells(usize::MAX),
+            usize::MAX / 2 + 1
+        );
+        assert_eq!(Permille::new(0).filled_cells(usize::MAX), 0);
+    }
+
+    #[test]
+    fn test_is_above() {
+        let threshold = Permille::new(900);
+        assert!(!Permille::new(899).is_above(threshold));
+        assert!(!Permille::new(900).is_above(threshold));
+        assert!(Permille::new(901).is_above(threshold));
+        assert!(Permille::FULL.is_above(threshold));
+        assert!(!Permille::FULL.is_above(Permille::FULL));
+        assert!(!Permille::EMPTY.is_above(Permille::EMPTY));
+        assert_eq!(Permille::FULL, Permille::new(1000));
+        assert_eq!(Permille::EMPTY, Permille::default());
+    }
+}
@@ -0,0 +1,86 @@
+//! Miscellaneous helpers for displaying values to the user
+
+const UNITS: &[(u64, &str)] = &[
+    (1_000, "k"),
+    (1_000_000, "M"),
+    (1_000_000_000, "G"),
+    (1_000_000_000_000, "T"),
+];
+
+/// Formats `n` in a short human-readable form with one digit after the decimal point, e.g. `987`,
+/// `15.3k` or `1.2M`
+///
+/// See [`humanize_count_with`] for details.
+#[inline]
+pub fn humanize_count(n: u64) -> String {
+    humanize_count_with(n, 1)
+}
+
+/// Formats `n` in a short human-readable form with `precision` digits after the decimal point
+///
+/// Numbers below 1000 are printed as is. Larger numbers are scaled down to the largest unit among
+/// `k`, `M`, `G` and `T` which keeps the value at least 1. If the value is rounded up to 1000, the
+/// next unit is used instead, so `999_999` is formatted as `1.0M` rather than `1000.0k`.
+pub fn humanize_count_with(n: u64, precision: usize) -> String {
+    if n < UNITS[0].0 {
+        return n.to_string();
+    }
+    // Rounding to more than 12 digits is exact, as `n / scale` has at most 12 fractional digits
+    let pow = 10_u128.pow(precision.min(12) as u32);
+    let &(scale, suffix) = UNITS
+        .iter()
+        .find(|&&(scale, _)| {
+            // `n / scale` rounded to `precision` digits and multiplied by `10^precision`
+            let scale = scale as u128;
+            let rounded = (n as u128 * pow + scale / 2) / scale;
+            rounded < 1000 * pow
+        })
+        .unwrap_or(UNITS.last().unwrap());
+    format!("{:.*}{}", precision, n as f64 / scale as f64, suffix)
+}
+
+/// Formats the nodes per second `n` as [`humanize_count`] does, followed by ` n/s`
+#[inline]
+pub fn humanize_nps(n: u64) -> String {
+    humanize_nps_with(n, 1)
+}
+
+/// Formats the nodes per second `n` as [`humanize_count_with`] does, followed by ` n/s`
+#[inline]
+pub fn humanize_nps_with(n: u64, precision: usize) -> String {
+    humanize_count_with(n, precision) + " n/s"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanize_count() {
+        assert_eq!(humanize_count(0), "0");
+        assert_eq!(humanize_count(987), "987");
+        assert_eq!(humanize_count(999), "999");
+        assert_eq!(humanize_count(1000), "1.0k");
+        assert_eq!(humanize_count(15_300), "15.3k");
+        assert_eq!(humanize_count(999_949), "999.9k");
+        assert_eq!(humanize_count(999_999), "1.0M");
+        assert_eq!(humanize_count(1_500_000), "1.5M");
+        assert_eq!(humanize_count(2_000_000_000), "2.0G");
+        assert_eq!(humanize_count(u64::MAX), "18446744.1T");
+        assert_eq!(humanize_count_with(1_234_567, 3), "1.235M");
+        assert_eq!(humanize_count_with(1_234_567, 0), "1M");
+        assert_eq!(humanize_count_with(999, 2), "999");
+        assert_eq!(humanize_count_with(999_994, 2), "999.99k");
+        assert_eq!(humanize_count_with(999_995, 2), "1.00M");
+        assert_eq!(humanize_count_with(999_500, 0), "1M");
+        assert_eq!(humanize_count_with(999_499, 0), "999k");
+        assert_eq!(humanize_count_with(999_999, 12), "999.999000000000k");
+    }
+
+    #[test]
+    fn test_humanize_nps() {
+        assert_eq!(humanize_nps(987), "987 n/s");
+        assert_eq!(humanize_nps(1_500_000), "1.5M n/s");
+        assert_eq!(humanize_nps_with(1_500_000, 2), "1.50M n/s");
+    }
+}
@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::Path;
+
+use wurm::CollectAll;
+
+use owlengine::uci::{
+    msg::Command,
+    parse::{CommandError, Fmt, Parse},
+};
+
+fn read_lines(name: &str) -> Vec<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("corpus")
+        .join(name);
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn test_commands() {
+    let src = read_lines("commands.txt");
+    let expected = read_lines("commands.expected");
+    assert_eq!(src.len(), expected.len());
+
+    for (line, expected) in src.iter().zip(expected.iter()) {
+        let mut warn = CollectAll::<CommandError>::default();
+        let cmd = Command::parse_line(line, &mut warn)
+            .unwrap_or_else(|| panic!("cannot parse {:?}", line));
+        assert!(warn.0.is_empty(), "warnings for {:?}: {:?}", line, warn.0);
+
+        let formatted = cmd.fmt_line();
+        assert_eq!(&formatted, expected);
+
+        let mut warn = CollectAll::<CommandError>::default();
+        let reparsed = Command::parse_line(&formatted, &mut warn);
+        assert!(
+            warn.0.is_empty(),
+            "warnings for {:?}: {:?}",
+            formatted,
+            warn.0
+        );
+        assert_eq!(reparsed, Some(cmd));
+    }
+}